@@ -0,0 +1,106 @@
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+use crate::finding::Finding;
+
+// Version of the baseline file format
+static BASELINE_VERSION: u32 = 1;
+
+#[derive(Deserialize, Serialize)]
+struct BaselineFile {
+    version: u32,
+    findings: Vec<BaselineEntry>,
+}
+
+// A known finding, the other fields only help reading the file
+#[derive(Deserialize, Serialize)]
+struct BaselineEntry {
+    fingerprint: String,
+    #[serde(default)]
+    rule_id: String,
+    #[serde(default)]
+    file: String,
+    #[serde(default)]
+    function: Option<String>,
+}
+
+// Findings accepted by a previous run
+// every entry matches a single finding, so a second identical finding is new
+#[derive(Clone)]
+pub struct Baseline {
+    remaining: HashMap<String, Remaining>,
+}
+
+// Entries of a fingerprint not matched yet, with the rule and file they were found by and in
+#[derive(Clone)]
+struct Remaining {
+    count: usize,
+    rule_id: String,
+    file: String,
+}
+
+impl Baseline {
+    // reads a baseline written by --write-baseline
+    pub fn load(path: &Path) -> Result<Baseline, String> {
+        let contents = fs::read_to_string(path).map_err(|err| format!("(load_baseline) - Failed to read {}: {}", path.display(), err))?;
+        let baseline: BaselineFile = serde_json::from_str(&contents).map_err(|err| format!("(load_baseline) - {} is not a baseline file: {}", path.display(), err))?;
+        if baseline.version != BASELINE_VERSION {
+            return Err(format!("(load_baseline) - {} has version {}, expected {}", path.display(), baseline.version, BASELINE_VERSION));
+        }
+        let mut remaining = HashMap::new();
+        for entry in baseline.findings {
+            remaining.entry(entry.fingerprint).or_insert(Remaining { count: 0, rule_id: entry.rule_id, file: entry.file }).count += 1;
+        }
+        Ok(Baseline { remaining })
+    }
+
+    // returns true if the finding is in the baseline, using up its entry
+    pub fn take(&mut self, finding: &Finding) -> bool {
+        match self.remaining.get_mut(&fingerprint(finding)) {
+            Some(known) if known.count > 0 => {
+                known.count -= 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    // returns how many findings of the baseline were not found again, counting only
+    // those whose rule and file were scanned: the others were filtered out, not fixed
+    // `scanned` is given a rule id and a file path; entries without them always count
+    pub fn fixed(&self, scanned: impl Fn(&str, &str) -> bool) -> usize {
+        self.remaining.values()
+            .filter(|known| known.rule_id.is_empty() || known.file.is_empty() || scanned(&known.rule_id, &known.file))
+            .map(|known| known.count)
+            .sum()
+    }
+}
+
+// returns the fingerprint of a finding: a hash of its rule id, file, enclosing
+// function and snippet with whitespace collapsed, so that it survives code moving around
+pub fn fingerprint(finding: &Finding) -> String {
+    let snippet = finding.snippet.split_whitespace().collect::<Vec<_>>().join(" ");
+    let key = [finding.rule_id.as_str(), &finding.file.replace('\\', "/"), &finding.scope().unwrap_or_default(), &snippet].join("\0");
+    format!("{:016x}", fnv1a(key.as_bytes()))
+}
+
+// 64-bit FNV-1a, stable across platforms and releases
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| (hash ^ *byte as u64).wrapping_mul(0x100000001b3))
+}
+
+// writes the findings of a run as a baseline
+pub fn write_baseline(path: &Path, findings: &[Finding]) -> Result<(), String> {
+    let baseline = BaselineFile {
+        version: BASELINE_VERSION,
+        findings: findings.iter().map(|finding| BaselineEntry {
+            fingerprint: fingerprint(finding),
+            rule_id: finding.rule_id.clone(),
+            file: finding.file.clone(),
+            function: finding.scope(),
+        }).collect(),
+    };
+    let file = File::create(path).map_err(|err| format!("(write_baseline) - It was not possible to create {}: {}", path.display(), err))?;
+    serde_json::to_writer_pretty(file, &baseline).map_err(|err| format!("(write_baseline) - Write failed: {}", err))
+}
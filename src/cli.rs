@@ -0,0 +1,154 @@
+use std::path::PathBuf;
+use clap::{Parser, Subcommand, ValueEnum};
+use crate::discovery::DiscoveryOptions;
+use crate::regexrules::Severity;
+
+// Command-line options for a scan
+#[derive(Parser, Debug)]
+#[command(name = "auditooor", version, about = "Static analysis of Solidity contracts for gas, low and non-critical issues", args_conflicts_with_subcommands = true)]
+pub struct Args {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Solidity files or directories to scan
+    #[arg(value_name = "TARGET", default_value = "test_contracts")]
+    pub targets: Vec<PathBuf>,
+
+    /// Directory where the report files are written
+    #[arg(long, value_name = "DIR", default_value = ".")]
+    pub out_dir: PathBuf,
+
+    /// Also scan test directories and `*.t.sol` files
+    #[arg(long)]
+    pub include_tests: bool,
+
+    /// Also scan dependency directories (`lib`, `node_modules`, ...)
+    #[arg(long)]
+    pub include_deps: bool,
+
+    /// Only analyze the files listed in a scope file (paths, globs or a contest README table)
+    #[arg(long, value_name = "FILE")]
+    pub scope: Option<PathBuf>,
+
+    /// Report formats to write (comma separated)
+    #[arg(long, value_enum, value_delimiter = ',', value_name = "FORMAT", default_value = "markdown")]
+    pub format: Vec<OutputFormat>,
+
+    /// Repository URL used to link contest report instances, e.g. `https://github.com/org/repo`
+    #[arg(long, value_name = "URL", requires = "commit")]
+    pub repo_url: Option<String>,
+
+    /// Commit hash used to link contest report instances
+    #[arg(long, value_name = "SHA", requires = "repo_url")]
+    pub commit: Option<String>,
+
+    /// Only run the given severities (comma separated)
+    #[arg(long, value_enum, value_delimiter = ',', value_name = "SEVERITY")]
+    pub only: Vec<Severity>,
+
+    /// Skip the given rule ids, e.g. `G-06` (comma separated, repeatable)
+    #[arg(long, value_delimiter = ',', value_name = "RULE_ID")]
+    pub exclude_rule: Vec<String>,
+
+    #[command(flatten)]
+    pub packs: RulePacks,
+
+    /// Project configuration with rule severities and disabled rules (default: `auditooor.toml` if present)
+    #[arg(long, value_name = "FILE")]
+    pub config: Option<PathBuf>,
+
+    /// Number of files analyzed in parallel (default: number of CPUs)
+    #[arg(short, long, value_name = "N", value_parser = clap::value_parser!(usize))]
+    pub jobs: Option<usize>,
+
+    /// Only report findings on lines changed since a git ref (`-` reads a unified diff from stdin)
+    #[arg(long, value_name = "BASE_REF")]
+    pub diff: Option<String>,
+
+    /// With --diff, report every finding of a function or modifier with a changed line
+    #[arg(long, requires = "diff")]
+    pub diff_whole_function: bool,
+
+    /// Only report the findings that are not in this baseline file
+    #[arg(long, value_name = "FILE")]
+    pub baseline: Option<PathBuf>,
+
+    /// Write every finding of this run to a baseline file
+    #[arg(long, value_name = "FILE")]
+    pub write_baseline: Option<PathBuf>,
+
+    /// Exit with status 1 when a finding of this severity or above is found
+    #[arg(long, value_enum, value_name = "SEVERITY")]
+    pub fail_on: Option<Severity>,
+
+    /// Keep running and re-analyze the files that change
+    #[arg(long)]
+    pub watch: bool,
+
+    /// Do not print the banner
+    #[arg(long)]
+    pub no_banner: bool,
+
+    /// Only print errors (implies --no-banner)
+    #[arg(short, long)]
+    pub quiet: bool,
+}
+
+// Where the rules are loaded from
+#[derive(clap::Args, Debug)]
+pub struct RulePacks {
+    /// Load an extra rule pack (TOML, YAML or JSON, repeatable)
+    #[arg(long = "rules", value_name = "FILE")]
+    pub rule_files: Vec<PathBuf>,
+
+    /// Load every rule pack of a directory (repeatable)
+    #[arg(long, value_name = "DIR")]
+    pub rules_dir: Vec<PathBuf>,
+
+    /// Do not load the built-in rule pack
+    #[arg(long)]
+    pub no_default_rules: bool,
+}
+
+// Commands run instead of a scan
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Check every rule against its `should_match` and `should_not_match` snippets, and that rule ids are unique
+    TestRules {
+        #[command(flatten)]
+        packs: RulePacks,
+    },
+}
+
+// Formats the results can be written in
+#[derive(Clone, Copy, Debug, Eq, PartialEq, ValueEnum)]
+pub enum OutputFormat {
+    #[value(alias = "md")]
+    Markdown,
+    Json,
+    Sarif,
+    // consolidated QA and Gas reports for contest submissions
+    Contest,
+}
+
+impl Args {
+    // returns true if the given severity was selected with --only
+    pub fn wants(&self, severity: Severity) -> bool {
+        self.only.is_empty() || self.only.contains(&severity)
+    }
+
+    // which parts of the targets are scanned
+    pub fn discovery_options(&self) -> DiscoveryOptions {
+        DiscoveryOptions { include_tests: self.include_tests, include_deps: self.include_deps }
+    }
+
+    // returns true if the given report format was requested with --format
+    pub fn writes(&self, format: OutputFormat) -> bool {
+        self.format.contains(&format)
+    }
+
+    // returns true if the given rule id was excluded with --exclude-rule
+    pub fn excludes(&self, rule_id: &str) -> bool {
+        self.exclude_rule.iter().any(|id| id.eq_ignore_ascii_case(rule_id))
+    }
+}
@@ -0,0 +1,59 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use serde::Deserialize;
+use crate::regexrules::{Rule, Severity};
+
+// Project configuration read from the working directory when --config is not given
+pub static CONFIG_NAME: &str = "auditooor.toml";
+
+// Per-project settings of the rules: which ones are turned off, which
+// ones are reported with another severity than their rule pack gives them,
+// and the project's own access control modifiers for the detectors
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    #[serde(default)]
+    pub disable: Vec<String>,
+    #[serde(default)]
+    pub severity: BTreeMap<String, Severity>,
+    #[serde(default)]
+    pub access_modifiers: Vec<String>,
+}
+
+impl Config {
+    // loads a config file, or auditooor.toml of the working directory if there is one
+    // returns the default config when neither exists
+    pub fn load(path: Option<&Path>) -> Result<Config, String> {
+        let path = match path {
+            Some(path) => path,
+            None if Path::new(CONFIG_NAME).is_file() => Path::new(CONFIG_NAME),
+            None => return Ok(Config::default()),
+        };
+        let contents = fs::read_to_string(path).map_err(|err| format!("(load_config) - Failed to read {}: {}", path.display(), err))?;
+        toml::from_str(&contents).map_err(|err| format!("(load_config) - Failed to parse {}: {}", path.display(), err))
+    }
+
+    // returns true if the given rule id was disabled
+    pub fn disables(&self, rule_id: &str) -> bool {
+        self.disable.iter().any(|id| id.eq_ignore_ascii_case(rule_id))
+    }
+
+    // gives a rule the severity configured for it, if any
+    pub fn apply(&self, rule: &mut Rule) {
+        if let Some((_, severity)) = self.severity.iter().find(|(id, _)| id.eq_ignore_ascii_case(&rule.id)) {
+            rule.severity = *severity;
+        }
+    }
+
+    // returns the rule ids of the config that match none of the given rules
+    pub fn unknown_ids<'a>(&'a self, rules: &[&Rule]) -> Vec<&'a str> {
+        let mut unknown: Vec<&str> = self.disable.iter().chain(self.severity.keys())
+            .filter(|id| !rules.iter().any(|rule| rule.id.eq_ignore_ascii_case(id)))
+            .map(|id| id.as_str())
+            .collect();
+        unknown.sort();
+        unknown.dedup();
+        unknown
+    }
+}
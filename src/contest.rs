@@ -0,0 +1,186 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use crate::discovery::{display_path, SourceFile};
+use crate::finding::Finding;
+use crate::regexrules::{Rule, Severity};
+use crate::scope::ScopeSummary;
+
+// Names of the consolidated reports written into the output directory
+pub static HM_REPORT_NAME: &str = "High and Medium Report.md";
+pub static QA_REPORT_NAME: &str = "QA Report.md";
+pub static GAS_REPORT_NAME: &str = "Gas Report.md";
+
+// Where the scanned code is published, used to build permalinks
+// `root` is the root of the git checkout of the scanned code, permalink paths start from it
+pub struct Repository<'a> {
+    url: &'a str,
+    commit: &'a str,
+    root: PathBuf,
+}
+
+impl<'a> Repository<'a> {
+    // finds the root of the git checkout of a scanned file or directory with `git rev-parse --show-toplevel`
+    pub fn new(url: &'a str, commit: &'a str, target: &Path) -> Result<Repository<'a>, String> {
+        let dir = if target.is_dir() { target } else { target.parent().filter(|parent| !parent.as_os_str().is_empty()).unwrap_or(Path::new(".")) };
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(dir)
+            .args(["rev-parse", "--show-toplevel"])
+            .output()
+            .map_err(|err| format!("(repository) - Failed to run git: {}", err))?;
+        if !output.status.success() {
+            return Err(format!("(repository) - --repo-url needs a git checkout to link files from its root: {}", String::from_utf8_lossy(&output.stderr).trim()));
+        }
+        let root = PathBuf::from(String::from_utf8_lossy(&output.stdout).trim());
+        let root = root.canonicalize().map_err(|err| format!("(repository) - Failed to resolve {}: {}", root.display(), err))?;
+        Ok(Repository { url, commit, root })
+    }
+
+    // returns the path of a scanned file from the root of the repository,
+    // or as it was scanned when it is outside of the repository
+    fn path(&self, file: &str) -> String {
+        Path::new(file).canonicalize().ok()
+            .and_then(|path| path.strip_prefix(&self.root).ok().map(display_path))
+            .unwrap_or_else(|| file.to_string())
+    }
+
+    // returns the permalink of a range of lines, e.g. `.../blob/<commit>/src/A.sol#L12-L14`
+    fn permalink(&self, finding: &Finding) -> String {
+        let lines = if finding.end_line > finding.start_line {
+            format!("L{}-L{}", finding.start_line, finding.end_line)
+        } else {
+            format!("L{}", finding.start_line)
+        };
+        format!("{}/blob/{}/{}#{}", self.url.trim_end_matches('/'), self.commit, self.path(&finding.file), lines)
+    }
+}
+
+// Lines of the scanned files, read once per file
+struct SourceLines<'a> {
+    files: &'a [SourceFile],
+    cache: HashMap<String, Vec<String>>,
+}
+
+impl SourceLines<'_> {
+    // returns the trimmed source line, or an empty string if it can not be read
+    fn line(&mut self, file: &str, line: usize) -> String {
+        let files = self.files;
+        let lines = self.cache.entry(file.to_string()).or_insert_with(|| {
+            files.iter().find(|source_file| source_file.relative == file)
+                .and_then(|source_file| fs::read_to_string(&source_file.path).ok())
+                .map(|source| source.lines().map(|line| line.to_string()).collect())
+                .unwrap_or_default()
+        });
+        lines.get(line - 1).map(|line| line.trim_end().to_string()).unwrap_or_default()
+    }
+}
+
+// returns the rules of the given severities that have findings, with their findings
+fn issues<'a>(rules: &[&'a Rule], findings: &'a [Finding], severities: &[Severity]) -> Vec<(&'a Rule, Vec<&'a Finding>)> {
+    rules.iter()
+        .filter(|rule| severities.contains(&rule.severity))
+        .map(|rule| (*rule, findings.iter().filter(|finding| finding.rule_id == rule.id).collect::<Vec<_>>()))
+        .filter(|(_, rule_findings)| !rule_findings.is_empty())
+        .collect()
+}
+
+// writes the summary table of a group of issues
+fn summary_table(title: &str, issues: &[(&Rule, Vec<&Finding>)]) -> String {
+    let mut table = format!("### {}\n\n| | Issue | Instances |\n| --- | :--- | :---: |\n", title);
+    for (rule, rule_findings) in issues {
+        table.push_str(&format!("| [{}] | {} | {} |\n", rule.id, rule.title, rule_findings.len()));
+    }
+    let total: usize = issues.iter().map(|(_, rule_findings)| rule_findings.len()).sum();
+    table.push_str(&format!("\nTotal: {} instances over {} issues\n\n", total, issues.len()));
+    table
+}
+
+// writes one section per issue, with every instance grouped by file
+fn issue_sections(issues: &[(&Rule, Vec<&Finding>)], lines: &mut SourceLines, repository: Option<&Repository>) -> String {
+    let mut sections = String::new();
+    for (rule, rule_findings) in issues {
+        sections.push_str(&format!("## [{}] {}\n\n", rule.id, rule.title));
+        if !rule.description.is_empty() {
+            sections.push_str(&format!("{}\n\n", rule.description));
+        }
+        sections.push_str(&format!("*There are {} instance(s) of this issue:*\n\n", rule_findings.len()));
+
+        let mut files: Vec<&str> = vec![];
+        for finding in rule_findings {
+            if !files.contains(&finding.file.as_str()) {
+                files.push(&finding.file);
+            }
+        }
+        for file in files {
+            let file_findings: Vec<&&Finding> = rule_findings.iter().filter(|finding| finding.file == file).collect();
+            sections.push_str(&format!("```solidity\nFile: {}\n\n", file));
+            for finding in &file_findings {
+                sections.push_str(&format!("{}: {}\n", finding.start_line, lines.line(file, finding.start_line)));
+            }
+            sections.push_str("```\n");
+            let links: Vec<String> = file_findings.iter().map(|finding| {
+                let link = match repository {
+                    Some(repository) => format!("[{}#L{}]({})", file, finding.start_line, repository.permalink(finding)),
+                    None => format!("`{}#L{}`", file, finding.start_line),
+                };
+                match finding.scope() {
+                    Some(scope) => format!("{} in `{}`", link, scope),
+                    None => link,
+                }
+            }).collect();
+            sections.push_str(&format!("{}\n\n", links.join(", ")));
+        }
+        if !rule.recommendation.is_empty() {
+            sections.push_str(&format!("**Recommendation:** {}\n\n", rule.recommendation));
+        }
+    }
+    sections
+}
+
+// writes the consolidated High and Medium, QA (low, non-critical and informational) and Gas reports,
+// grouped by rule across all files
+// a report is only written when rules of its severities ran
+pub fn write_contest_reports(out_dir: &Path, rules: &[&Rule], findings: &[Finding], files: &[SourceFile], scope: Option<&ScopeSummary>, repository: Option<&Repository>) -> Result<(), String> {
+    let mut lines = SourceLines { files, cache: HashMap::new() };
+    let header = scope.map(|scope| scope.markdown_header()).unwrap_or_default();
+    let ran = |severity: Severity| rules.iter().any(|rule| rule.severity == severity);
+
+    let high = issues(rules, findings, &[Severity::High]);
+    let medium = issues(rules, findings, &[Severity::Medium]);
+    let mut hm_report = format!("# High and Medium Report\n\n{}## Summary\n\n", header);
+    hm_report.push_str(&summary_table("High Risk Issues", &high));
+    hm_report.push_str(&summary_table("Medium Risk Issues", &medium));
+    hm_report.push_str(&issue_sections(&high, &mut lines, repository));
+    hm_report.push_str(&issue_sections(&medium, &mut lines, repository));
+
+    let low = issues(rules, findings, &[Severity::Low]);
+    let non_critical = issues(rules, findings, &[Severity::NonCritical]);
+    let informational = issues(rules, findings, &[Severity::Informational]);
+    let mut qa_report = format!("# QA Report\n\n{}## Summary\n\n", header);
+    qa_report.push_str(&summary_table("Low Risk Issues", &low));
+    qa_report.push_str(&summary_table("Non-Critical Issues", &non_critical));
+    if ran(Severity::Informational) {
+        qa_report.push_str(&summary_table("Informational Issues", &informational));
+    }
+    qa_report.push_str(&issue_sections(&low, &mut lines, repository));
+    qa_report.push_str(&issue_sections(&non_critical, &mut lines, repository));
+    qa_report.push_str(&issue_sections(&informational, &mut lines, repository));
+
+    let gas = issues(rules, findings, &[Severity::Gas]);
+    let mut gas_report = format!("# Gas Report\n\n{}## Summary\n\n", header);
+    gas_report.push_str(&summary_table("Gas Optimizations", &gas));
+    gas_report.push_str(&issue_sections(&gas, &mut lines, repository));
+
+    let reports = [
+        (HM_REPORT_NAME, hm_report, ran(Severity::High) || ran(Severity::Medium)),
+        (QA_REPORT_NAME, qa_report, ran(Severity::Low) || ran(Severity::NonCritical) || ran(Severity::Informational)),
+        (GAS_REPORT_NAME, gas_report, ran(Severity::Gas)),
+    ];
+    for (name, report, _) in reports.into_iter().filter(|(_, _, wanted)| *wanted) {
+        let path = out_dir.join(name);
+        fs::write(&path, report).map_err(|err| format!("(write_contest_reports) - It was not possible to write {}: {}", path.display(), err))?;
+    }
+    Ok(())
+}
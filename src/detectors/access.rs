@@ -0,0 +1,179 @@
+use crate::regexrules::{Rule, Severity};
+use crate::solidity::ast::*;
+use super::{identifiers, is_initialization_name, is_restricted, rule, snippets, words, Detection, Detector, Locals};
+
+// Words in the names of state variables that only privileged users should set
+static SENSITIVE_WORDS: [&str; 5] = ["owner", "admin", "fee", "oracle", "treasury"];
+
+// H-02: `external`/`public` functions changing privileged state without checking the caller
+pub struct MissingAccessControl {
+    rule: Rule,
+    // access control modifiers of the project, from the `access_modifiers` of auditooor.toml
+    modifiers: Vec<String>,
+}
+
+impl MissingAccessControl {
+    pub fn new(modifiers: &[String]) -> MissingAccessControl {
+        MissingAccessControl {
+            rule: Rule {
+                should_match: snippets(&[
+                    "contract A { address public owner; function setOwner(address newOwner) external { owner = newOwner; } }",
+                    "contract A { uint256 public feeBps; function setFee(uint256 bps) public { require(bps <= 1000); feeBps = bps; } }",
+                    "contract A { function kill() external { selfdestruct(payable(msg.sender)); } }",
+                    "contract A { function burnFrom(address account, uint256 amount) external { _burn(account, amount); } }",
+                    "contract A { address public owner; function setOwner(address newOwner) external { require(balances[msg.sender] > 0); owner = newOwner; } }",
+                ]),
+                should_not_match: snippets(&[
+                    "contract A { address public owner; function setOwner(address newOwner) external onlyOwner { owner = newOwner; } }",
+                    "contract A { address public owner; function setOwner(address newOwner) external { require(msg.sender == owner, \"not owner\"); owner = newOwner; } }",
+                    "contract A { address public owner; modifier isGovernor() { if (msg.sender != governor) revert(); _; } function setOwner(address newOwner) external isGovernor { owner = newOwner; } }",
+                    "contract A { address public treasury; function setTreasury(address newTreasury) external { _checkOwner(); treasury = newTreasury; } }",
+                    "contract A { address public treasury; function setTreasury(address newTreasury) external { require(hasRole(ADMIN_ROLE, msg.sender)); treasury = newTreasury; } }",
+                    "contract A { address public treasury; function setTreasury(address newTreasury) external { require(authorized[msg.sender], \"unauthorized\"); treasury = newTreasury; } }",
+                    "contract A { uint256 public fees; function swap(uint256 amount) external { fees += amount / 100; } }",
+                    "contract A { function burn(uint256 amount) external { _burn(msg.sender, amount); } }",
+                    "contract A { function mint(uint256 shares, address receiver) external { asset.safeTransferFrom(msg.sender, address(this), shares); _mint(receiver, shares); } }",
+                    "contract A { function burnFrom(address account, uint256 value) public virtual { _spendAllowance(account, _msgSender(), value); _burn(account, value); } }",
+                    "contract A { uint256 public feeGrowthGlobal; function swap(uint256 amount) external { feeGrowthGlobal = feeGrowthGlobal + amount / 100; } }",
+                    "contract A { address public priceFeedUpdater; function poke(address updater) external { priceFeedUpdater = updater; } }",
+                ]),
+                ..rule(
+                    "H-02",
+                    Severity::High,
+                    "Missing access control on a privileged function",
+                    "The function can be called by anyone, yet it changes privileged state (the owner, admins, fees, oracles or treasury), destroys the contract, delegates calls, upgrades the implementation, or mints or burns tokens of any account. It has no access control modifier and never checks `msg.sender`.",
+                    "Restrict the function with an access control modifier such as `onlyOwner` or `onlyRole`, or check `msg.sender`. If it is restricted by a modifier of the project that is not recognised, add the modifier to `access_modifiers` in auditooor.toml.",
+                    &["access-control"],
+                )
+            },
+            modifiers: modifiers.to_vec(),
+        }
+    }
+}
+
+impl Detector for MissingAccessControl {
+    fn rule(&self) -> &Rule {
+        &self.rule
+    }
+
+    fn detect(&self, _source: &str, unit: &SourceUnit) -> Vec<Detection> {
+        let mut detections = vec![];
+        for contract in &unit.contracts {
+            let sensitive: Vec<&str> = contract.state_variables.iter()
+                .filter(|variable| !matches!(variable.mutability, Some(Mutability::Constant | Mutability::Immutable)))
+                .filter(|variable| is_sensitive(&variable.name, &variable.type_name))
+                .map(|variable| variable.name.as_str())
+                .collect();
+            let read_only = |function: &Function| matches!(function.mutability, Some(Mutability::View | Mutability::Pure));
+            // initialization functions are checked by H-03
+            let initializer = |function: &Function| {
+                function.has_modifier("initializer") || function.has_modifier("reinitializer") || function.name.as_deref().is_some_and(is_initialization_name)
+            };
+            for function in contract.functions.iter().filter(|function| function.kind == FunctionKind::Function && function.is_external() && !read_only(function) && !initializer(function)) {
+                let Some(body) = &function.body else { continue };
+                if is_restricted(function, contract, &self.modifiers) {
+                    continue;
+                }
+                let locals = Locals::of(function);
+                let parameters: Vec<&str> = function.parameters.iter().filter_map(|parameter| parameter.name.as_deref()).collect();
+                let mut operations = Operations { sensitive: &sensitive, locals: &locals.names, parameters: &parameters, found: vec![], pulls_tokens: false, spends_allowance: false, mints: vec![] };
+                walk_block(&mut operations, body);
+                // minting and burning for someone who pays for it, e.g. ERC4626 `mint()`,
+                // or who approved the caller, e.g. ERC20Burnable `burnFrom()`, is not privileged
+                if !operations.pulls_tokens && !operations.spends_allowance {
+                    operations.found.append(&mut operations.mints);
+                }
+                if !operations.found.is_empty() {
+                    operations.found.sort_by_key(|(span, _)| span.start);
+                    detections.push(Detection { span: function.header, related: operations.found });
+                }
+            }
+        }
+        detections
+    }
+}
+
+// returns true if a state variable holds privileged settings: any variable with one of the
+// sensitive words in its name, but for mappings only admin ones (token owners are not privileged)
+fn is_sensitive(name: &str, type_name: &TypeName) -> bool {
+    let words = words(name);
+    let has_word = |word: &str| words.iter().any(|name_word| name_word == word || name_word.strip_suffix('s') == Some(word));
+    match type_name {
+        TypeName::Mapping => has_word("admin"),
+        _ => SENSITIVE_WORDS.iter().any(|word| has_word(word)),
+    }
+}
+
+// Collects the privileged operations of a function body, each with what it does
+struct Operations<'a> {
+    sensitive: &'a [&'a str],
+    locals: &'a [String],
+    parameters: &'a [&'a str],
+    found: Vec<(Span, String)>,
+    // mints and burns for an account given by the caller, and whether tokens are pulled from
+    // the caller or an allowance given to the caller is spent
+    mints: Vec<(Span, String)>,
+    pulls_tokens: bool,
+    spends_allowance: bool,
+}
+
+impl Operations<'_> {
+    // records a write of a sensitive state variable
+    fn written(&mut self, expression: &Expression, target: &Expression) {
+        if let Some(name) = target.root_identifier() {
+            if self.sensitive.contains(&name) && !self.locals.iter().any(|local| local == name) {
+                self.found.push((expression.span, format!("`{}` written", name)));
+            }
+        }
+    }
+}
+
+impl Visitor for Operations<'_> {
+    fn visit_expression(&mut self, expression: &Expression) {
+        match &expression.kind {
+            // `owner = newOwner`; assignments reading the variable, `fees += fee` or `fees = fees + fee`, accumulate instead of setting it
+            ExpressionKind::Assign(operator, target, value) if operator == "=" => {
+                let accumulates = target.root_identifier().is_some_and(|name| identifiers(value).iter().any(|(read, _)| read == name));
+                if !accumulates {
+                    self.written(expression, target);
+                }
+                // `allowance[owner][msg.sender] = allowed - amount`
+                if target.root_identifier().is_some_and(|name| name.to_lowercase().contains("allowance")) {
+                    self.spends_allowance = true;
+                }
+            }
+            // `allowance[owner][msg.sender] -= amount`
+            ExpressionKind::Assign(operator, target, _) if operator == "-=" && target.root_identifier().is_some_and(|name| name.to_lowercase().contains("allowance")) => {
+                self.spends_allowance = true;
+            }
+            // `delete oracle`
+            ExpressionKind::Unary(operator, target) if operator == "delete" => self.written(expression, target),
+            ExpressionKind::Call(callee, arguments) => {
+                let name = match &callee.kind {
+                    ExpressionKind::Identifier(name) | ExpressionKind::Member(_, name) => name.as_str(),
+                    ExpressionKind::CallOptions(target, _) => target.member_name().unwrap_or_default(),
+                    _ => "",
+                };
+                match name {
+                    "selfdestruct" | "suicide" => self.found.push((expression.span, format!("`{}` called", name))),
+                    "delegatecall" if callee.member_name().is_some() => self.found.push((expression.span, "`delegatecall` made".to_string())),
+                    "upgradeTo" | "upgradeToAndCall" | "_upgradeTo" | "_upgradeToAndCall" | "_upgradeToAndCallUUPS" => {
+                        self.found.push((expression.span, format!("`{}` called", name)))
+                    }
+                    "transferFrom" | "safeTransferFrom" => self.pulls_tokens = true,
+                    "_spendAllowance" | "_approve" | "_isApprovedOrOwner" | "_checkAuthorized" | "_isAuthorized" => self.spends_allowance = true,
+                    "mint" | "_mint" | "burn" | "_burn" => {
+                        // the account is chosen by the caller, instead of being the caller itself
+                        let for_anyone = arguments.first().and_then(|account| account.root_identifier()).is_some_and(|account| self.parameters.contains(&account));
+                        if for_anyone {
+                            self.mints.push((expression.span, format!("`{}` called for any account", name)));
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+        walk_expression(self, expression);
+    }
+}
@@ -0,0 +1,52 @@
+use crate::regexrules::{Rule, Severity};
+use crate::solidity::ast::*;
+use super::{functions, rule, snippets, Detection, Detector, Writes};
+
+// G-10: `memory` parameters of `external` functions that are never written to
+pub struct MemoryParameters {
+    rule: Rule,
+}
+
+impl MemoryParameters {
+    pub fn new() -> MemoryParameters {
+        MemoryParameters {
+            rule: Rule {
+                should_match: snippets(&["contract A { function total(uint256[] memory amounts) external returns (uint256) { return amounts.length; } }"]),
+                should_not_match: snippets(&[
+                    "contract A { function total(uint256[] calldata amounts) external returns (uint256) { return amounts.length; } }",
+                    "contract A { function total(uint256[] memory amounts) external returns (uint256) { amounts[0] = 1; return amounts.length; } }",
+                ]),
+                ..rule(
+                    "G-10",
+                    Severity::Gas,
+                    "Use `calldata` instead of `memory` for read-only arguments in `external` functions.",
+                    "When a function with a `memory` array is called externally, the `abi.decode()` step has to use a for-loop to copy each index of the `calldata` to the `memory` index. Each iteration of this for-loop costs at least 60 gas (i.e. 60 * <mem_array>.length). Using calldata directly, obliviates the need for such a loop in the contract code and runtime execution.",
+                    "Use `calldata` instead of `memory`.",
+                    &["functions", "calldata"],
+                )
+            },
+        }
+    }
+}
+
+impl Detector for MemoryParameters {
+    fn rule(&self) -> &Rule {
+        &self.rule
+    }
+
+    fn detect(&self, _source: &str, unit: &SourceUnit) -> Vec<Detection> {
+        let mut spans = vec![];
+        for function in functions(unit).filter(|function| function.visibility == Some(Visibility::External)) {
+            let Some(body) = &function.body else { continue };
+            let mut writes = Writes::default();
+            walk_block(&mut writes, body);
+            for parameter in &function.parameters {
+                let read_only = parameter.name.as_ref().is_none_or(|name| !writes.writes(name));
+                if parameter.location == Some(DataLocation::Memory) && read_only {
+                    spans.push(parameter.span.into());
+                }
+            }
+        }
+        spans
+    }
+}
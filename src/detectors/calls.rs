@@ -0,0 +1,135 @@
+use crate::regexrules::{Rule, Severity};
+use crate::solidity::ast::*;
+use super::{functions, identifiers, rule, snippets, Conditions, Detection, Detector, Writes};
+
+// M-01: low-level calls and `send` whose success flag is discarded or never checked
+pub struct UncheckedCalls {
+    rule: Rule,
+}
+
+impl UncheckedCalls {
+    pub fn new() -> UncheckedCalls {
+        UncheckedCalls {
+            rule: Rule {
+                should_match: snippets(&[
+                    "contract A { function pay(address to) external { to.call{value: 1 ether}(\"\"); } }",
+                    "contract A { function pay(address to) external { (bool ok, ) = to.call{value: 1 ether}(\"\"); } }",
+                    "contract A { function pay(address payable to) external { bool sent = to.send(1 ether); emit Paid(sent); } }",
+                    "contract A { function run(address target, bytes calldata data) external { (bool ok, ) = target.delegatecall(data); require(ok); (ok, ) = target.delegatecall(data); } }",
+                ]),
+                should_not_match: snippets(&[
+                    "contract A { function pay(address to) external { (bool ok, ) = to.call{value: 1 ether}(\"\"); require(ok, \"failed\"); } }",
+                    "contract A { function pay(address to) external { (bool ok, ) = to.call{value: 1 ether}(\"\"); if (!ok) revert Failed(); } }",
+                    "contract A { function pay(address payable to) external { require(to.send(1 ether)); } }",
+                    "contract A { function read(address target) external view returns (bool) { (bool ok, ) = target.staticcall(\"\"); return ok; } }",
+                    "contract A { function pay(address payable to) external { to.transfer(1 ether); } }",
+                ]),
+                ..rule(
+                    "M-01",
+                    Severity::Medium,
+                    "Unchecked return value of low-level `call`/`delegatecall`/`staticcall` or `send`",
+                    "Low-level calls and `send` do not revert when the callee fails: they return `false`. When the success flag is discarded, or captured and never tested, a failed transfer or call goes unnoticed and the function carries on as if it succeeded.",
+                    "Check the success flag, e.g. `(bool success, ) = to.call{value: amount}(\"\"); require(success);`, or use OpenZeppelin's `Address.sendValue()`/`Address.functionCall()`.",
+                    &["external-calls", "return-values"],
+                )
+            },
+        }
+    }
+}
+
+impl Detector for UncheckedCalls {
+    fn rule(&self) -> &Rule {
+        &self.rule
+    }
+
+    fn detect(&self, _source: &str, unit: &SourceUnit) -> Vec<Detection> {
+        let mut detections = vec![];
+        for function in functions(unit) {
+            let Some(body) = &function.body else { continue };
+            let mut calls = Calls::default();
+            walk_block(&mut calls, body);
+            if calls.discarded.is_empty() && calls.captured.is_empty() {
+                continue;
+            }
+            let mut writes = Writes::default();
+            walk_block(&mut writes, body);
+            let mut conditions = Conditions::default();
+            walk_block(&mut conditions, body);
+            // returning the flag leaves the check to the caller
+            let uses: Vec<(String, usize)> = conditions.tested.iter().chain(&conditions.returned).flat_map(identifiers).collect();
+
+            detections.extend(calls.discarded.into_iter().map(Detection::from));
+            for (call, name, variable) in calls.captured {
+                // the flag is checked before it is overwritten by another call
+                let overwritten = writes.targets.iter()
+                    .filter(|(target, span)| *target == name && span.start >= call.end)
+                    .map(|(_, span)| span.start)
+                    .min()
+                    .unwrap_or(usize::MAX);
+                let checked = uses.iter().any(|(used, offset)| *used == name && *offset >= call.end && *offset < overwritten);
+                if !checked {
+                    detections.push(Detection { span: call, related: vec![(variable, format!("`{}` is never checked", name))] });
+                }
+            }
+        }
+        detections.sort_by_key(|detection| detection.span.start);
+        detections
+    }
+}
+
+// returns true for `target.call(...)`, `target.delegatecall(...)`, `target.staticcall(...)`,
+// with or without call options, and `to.send(amount)`
+fn is_low_level_call(expression: &Expression) -> bool {
+    let ExpressionKind::Call(callee, arguments) = &expression.kind else { return false };
+    let callee = match &callee.kind {
+        ExpressionKind::CallOptions(callee, _) => callee,
+        _ => callee,
+    };
+    match callee.member_name() {
+        Some("call" | "delegatecall" | "staticcall") => true,
+        // ERC777 `send(to, amount, data)` reverts on failure
+        Some("send") => arguments.len() == 1,
+        _ => false,
+    }
+}
+
+// Collects the low-level calls of a function body whose result is thrown away,
+// and those whose success flag is stored, with the name and span of the flag
+#[derive(Default)]
+struct Calls {
+    discarded: Vec<Span>,
+    captured: Vec<(Span, String, Span)>,
+}
+
+impl Calls {
+    // records a call whose result is stored into `target`, the first tuple element being the success flag
+    fn store(&mut self, call: &Expression, target: Option<&Expression>) {
+        let target = match target.map(|target| &target.kind) {
+            Some(ExpressionKind::Tuple(elements)) => elements.first().and_then(|element| element.as_ref()),
+            _ => target,
+        };
+        match target.and_then(|target| target.root_identifier().map(|name| (name, target.span))) {
+            Some((name, span)) => self.captured.push((call.span, name.to_string(), span)),
+            None => self.discarded.push(call.span),
+        }
+    }
+}
+
+impl Visitor for Calls {
+    fn visit_statement(&mut self, statement: &Statement) {
+        match &statement.kind {
+            StatementKind::Expression(expression) if is_low_level_call(expression) => self.discarded.push(expression.span),
+            StatementKind::Expression(Expression { kind: ExpressionKind::Assign(operator, target, value), .. }) if operator == "=" && is_low_level_call(value) => {
+                self.store(value, Some(target));
+            }
+            StatementKind::VariableDeclaration(variables, Some(value)) if is_low_level_call(value) => {
+                match variables.first().and_then(|variable| variable.as_ref()) {
+                    Some(Parameter { name: Some(name), span, .. }) => self.captured.push((value.span, name.clone(), *span)),
+                    _ => self.store(value, None),
+                }
+            }
+            _ => {}
+        }
+        walk_statement(self, statement);
+    }
+}
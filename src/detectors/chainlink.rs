@@ -0,0 +1,521 @@
+use crate::regexrules::{Rule, Severity};
+use crate::solidity::ast::*;
+use super::{comparisons, functions, identifiers, rule, snippets, Conditions, Detection, Detector};
+
+// Indexes of the values returned by `latestRoundData()`
+static ROUND_ID: usize = 0;
+static ANSWER: usize = 1;
+static UPDATED_AT: usize = 3;
+static ANSWERED_IN_ROUND: usize = 4;
+
+// Words in the name of a function that validates its arguments, e.g. `_checkPrice(answer, updatedAt)`
+static VALIDATION_WORDS: [&str; 5] = ["check", "valid", "stale", "verify", "ensure"];
+
+// Name standing for the current time (`block.timestamp`, or the deprecated `now`) among the variables an expression reads
+static TIMESTAMP: &str = "block.timestamp";
+
+// Variables the results of a `latestRoundData()` call are stored into, in return order:
+// roundId, answer, startedAt, updatedAt, answeredInRound (None when discarded)
+type Results = Vec<Option<(String, Span)>>;
+
+// A `latestRoundData()` call and what its function does with the results
+struct Consumer {
+    call: Span,
+    results: Results,
+    // conditions of the function, the `if` conditions it reverts under, and the variables passed to validation functions
+    tested: Vec<Expression>,
+    reverting: Vec<Span>,
+    validated: Vec<String>,
+    // local variables computed from other variables, e.g. `age` from `block.timestamp - updatedAt`
+    flows: Vec<(String, Vec<String>)>,
+}
+
+impl Consumer {
+    // returns the variable a result is stored into, if it is not discarded
+    fn result(&self, index: usize) -> Option<&(String, Span)> {
+        self.results.get(index).and_then(|result| result.as_ref())
+    }
+
+    // returns the given names and the local variables computed from them
+    fn derived_from<'a>(&'a self, mut names: Vec<&'a str>) -> Vec<&'a str> {
+        let mut grown = true;
+        while grown {
+            grown = false;
+            for (target, sources) in &self.flows {
+                if !names.contains(&target.as_str()) && sources.iter().any(|source| names.contains(&source.as_str())) {
+                    names.push(target);
+                    grown = true;
+                }
+            }
+        }
+        names
+    }
+
+    // returns the variable of a result and the local variables computed from it
+    fn derived(&self, index: usize) -> Vec<&str> {
+        self.derived_from(self.result(index).map(|(name, _)| name.as_str()).into_iter().collect())
+    }
+
+    // returns true if a result is handed to a validation function, directly or through a local variable
+    fn is_validated(&self, index: usize) -> bool {
+        self.derived(index).iter().any(|name| self.validated.iter().any(|validated| validated == name))
+    }
+
+    // returns the comparisons of the conditions after the call as (reverts when true, operator, left, right)
+    fn comparisons(&self) -> Vec<(bool, &str, &Expression, &Expression)> {
+        self.tested.iter()
+            .filter(|condition| condition.span.start >= self.call.end)
+            .flat_map(|condition| {
+                let reverting = self.reverting.contains(&condition.span);
+                comparisons(condition).into_iter().map(move |(operator, left, right)| (reverting, operator, left, right))
+            })
+            .collect()
+    }
+
+    // returns the comparisons reading both a result and the current time, e.g. `block.timestamp - updatedAt <= heartbeat`
+    fn timestamp_comparisons(&self, index: usize) -> Vec<(&Expression, &Expression)> {
+        let names = self.derived(index);
+        let now = self.derived_from(vec![TIMESTAMP]);
+        self.comparisons().into_iter()
+            .map(|(_, _, left, right)| (left, right))
+            .filter(|(left, right)| {
+                let read: Vec<String> = reads(left).into_iter().chain(reads(right)).collect();
+                read.iter().any(|name| names.contains(&name.as_str())) && read.iter().any(|name| now.contains(&name.as_str()))
+            })
+            .collect()
+    }
+
+    // returns true if a result is required to be above a bound: `answer > 0`, `answer >= minPrice`, `if (answer <= 0) revert ...`
+    fn is_bounded_below(&self, index: usize) -> bool {
+        let names = self.derived(index);
+        self.comparisons().into_iter().any(|(reverting, operator, left, right)| {
+            let (greater, lesser) = if operator.starts_with('>') { (left, right) } else { (right, left) };
+            let bounded = if reverting { lesser } else { greater };
+            reads(bounded).iter().any(|name| names.contains(&name.as_str()))
+        })
+    }
+
+    // returns true if two results are compared with each other, directly or through local variables
+    fn is_compared(&self, index: usize, other: usize) -> bool {
+        let (names, others) = (self.derived(index), self.derived(other));
+        self.comparisons().into_iter().any(|(_, _, left, right)| {
+            let (left, right) = (reads(left), reads(right));
+            let reads_any = |read: &[String], names: &[&str]| read.iter().any(|name| names.contains(&name.as_str()));
+            (reads_any(&left, &names) && reads_any(&right, &others)) || (reads_any(&left, &others) && reads_any(&right, &names))
+        })
+    }
+
+    // reports the call, pointing at the variable of the result when it is not discarded
+    fn detection(&self, index: usize, message: &str) -> Detection {
+        Detection {
+            span: self.call,
+            related: self.result(index).map(|(name, span)| (*span, format!("`{}` {}", name, message))).into_iter().collect(),
+        }
+    }
+}
+
+// returns the variables an expression reads, with the current time as `TIMESTAMP`
+fn reads(expression: &Expression) -> Vec<String> {
+    #[derive(Default)]
+    struct Timestamp {
+        found: bool,
+    }
+    impl Visitor for Timestamp {
+        fn visit_expression(&mut self, expression: &Expression) {
+            match &expression.kind {
+                ExpressionKind::Member(base, member) if member == "timestamp" && matches!(&base.kind, ExpressionKind::Identifier(name) if name == "block") => self.found = true,
+                ExpressionKind::Identifier(name) if name == "now" => self.found = true,
+                _ => {}
+            }
+            walk_expression(self, expression);
+        }
+    }
+    let mut names: Vec<String> = identifiers(expression).into_iter().map(|(name, _)| name).collect();
+    let mut timestamp = Timestamp::default();
+    timestamp.visit_expression(expression);
+    if timestamp.found {
+        names.push(TIMESTAMP.to_string());
+    }
+    names
+}
+
+// returns true for `<feed>.latestRoundData()`
+fn is_latest_round_data(expression: &Expression) -> bool {
+    matches!(&expression.kind, ExpressionKind::Call(callee, _) if callee.member_name() == Some("latestRoundData"))
+}
+
+// returns every `latestRoundData()` call of a file whose results are stored into variables
+fn consumers(unit: &SourceUnit) -> Vec<Consumer> {
+    let mut consumers = vec![];
+    for function in functions(unit) {
+        let Some(body) = &function.body else { continue };
+        let mut bindings = Bindings::default();
+        walk_block(&mut bindings, body);
+        if bindings.calls.is_empty() {
+            continue;
+        }
+        let mut conditions = Conditions::default();
+        walk_block(&mut conditions, body);
+        let mut flows = Flows::default();
+        walk_block(&mut flows, body);
+        for (call, results) in bindings.calls {
+            consumers.push(Consumer {
+                call,
+                results,
+                tested: conditions.tested.clone(),
+                reverting: conditions.reverting.iter().map(|condition| condition.span).collect(),
+                validated: flows.validated.clone(),
+                flows: flows.flows.clone(),
+            });
+        }
+    }
+    consumers
+}
+
+// Collects the `latestRoundData()` calls of a function body with the variables their results are stored into:
+// `(, int256 answer, , uint256 updatedAt, ) = feed.latestRoundData();`, the same as an assignment,
+// or the returns of `try feed.latestRoundData() returns (...)`
+#[derive(Default)]
+struct Bindings {
+    calls: Vec<(Span, Results)>,
+}
+
+impl Visitor for Bindings {
+    fn visit_statement(&mut self, statement: &Statement) {
+        match &statement.kind {
+            StatementKind::VariableDeclaration(variables, Some(value)) if is_latest_round_data(value) => {
+                let results = variables.iter().map(|variable| variable.as_ref().and_then(|variable| variable.name.clone().map(|name| (name, variable.span)))).collect();
+                self.calls.push((value.span, results));
+            }
+            StatementKind::Expression(Expression { kind: ExpressionKind::Assign(_, target, value), .. }) if is_latest_round_data(value) => {
+                if let ExpressionKind::Tuple(elements) = &target.kind {
+                    let results = elements.iter().map(|element| element.as_ref().and_then(|element| element.root_identifier().map(|name| (name.to_string(), element.span)))).collect();
+                    self.calls.push((value.span, results));
+                }
+            }
+            StatementKind::Try(call, returns, ..) if is_latest_round_data(call) => {
+                let results = returns.iter().map(|variable| variable.name.clone().map(|name| (name, variable.span))).collect();
+                self.calls.push((call.span, results));
+            }
+            _ => {}
+        }
+        walk_statement(self, statement);
+    }
+}
+
+// Collects the local variables computed from other variables,
+// and the variables passed to validation functions
+#[derive(Default)]
+struct Flows {
+    flows: Vec<(String, Vec<String>)>,
+    validated: Vec<String>,
+}
+
+impl Flows {
+    fn add(&mut self, target: &str, value: &Expression) {
+        self.flows.push((target.to_string(), reads(value)));
+    }
+}
+
+impl Visitor for Flows {
+    fn visit_statement(&mut self, statement: &Statement) {
+        if let StatementKind::VariableDeclaration(variables, Some(value)) = &statement.kind {
+            for name in variables.iter().flatten().filter_map(|variable| variable.name.as_ref()) {
+                self.add(name, value);
+            }
+        }
+        walk_statement(self, statement);
+    }
+
+    fn visit_expression(&mut self, expression: &Expression) {
+        match &expression.kind {
+            ExpressionKind::Assign(_, target, value) => {
+                if let Some(name) = target.root_identifier() {
+                    self.add(name, value);
+                }
+            }
+            ExpressionKind::Call(callee, arguments) => {
+                let name = match &callee.kind {
+                    ExpressionKind::Identifier(name) | ExpressionKind::Member(_, name) => name.to_lowercase(),
+                    _ => String::new(),
+                };
+                if VALIDATION_WORDS.iter().any(|word| name.contains(word)) {
+                    self.validated.extend(arguments.iter().flat_map(identifiers).map(|(name, _)| name));
+                }
+            }
+            _ => {}
+        }
+        walk_expression(self, expression);
+    }
+}
+
+// Collects every identifier, member and declaration name of a file
+#[derive(Default)]
+struct Names {
+    names: Vec<String>,
+}
+
+impl Visitor for Names {
+    fn visit_contract(&mut self, contract: &Contract) {
+        self.names.push(contract.name.clone());
+        self.names.extend(contract.state_variables.iter().map(|variable| variable.name.clone()));
+        walk_contract(self, contract);
+    }
+
+    fn visit_function(&mut self, function: &Function) {
+        self.names.extend(function.name.clone());
+        self.names.extend(function.parameters.iter().filter_map(|parameter| parameter.name.clone()));
+        walk_function(self, function);
+    }
+
+    fn visit_expression(&mut self, expression: &Expression) {
+        match &expression.kind {
+            ExpressionKind::Identifier(name) | ExpressionKind::Member(_, name) => self.names.push(name.clone()),
+            _ => {}
+        }
+        walk_expression(self, expression);
+    }
+}
+
+// returns the numeric literals of an expression, other than `0`
+fn number_literals(expression: &Expression) -> Vec<Span> {
+    #[derive(Default)]
+    struct Numbers {
+        spans: Vec<Span>,
+    }
+    impl Visitor for Numbers {
+        fn visit_expression(&mut self, expression: &Expression) {
+            if let ExpressionKind::Literal(text) = &expression.kind {
+                if text.starts_with(|c: char| c.is_ascii_digit()) && text != "0" {
+                    self.spans.push(expression.span);
+                }
+            }
+            walk_expression(self, expression);
+        }
+    }
+    let mut numbers = Numbers::default();
+    numbers.visit_expression(expression);
+    numbers.spans
+}
+
+// M-02: `updatedAt` of `latestRoundData()` never checked, stale prices are accepted
+pub struct StalePrice {
+    rule: Rule,
+}
+
+impl StalePrice {
+    pub fn new() -> StalePrice {
+        StalePrice {
+            rule: Rule {
+                should_match: snippets(&[
+                    "contract A { function price() external view returns (int256) { (, int256 answer, , , ) = feed.latestRoundData(); require(answer > 0); return answer; } }",
+                    "contract A { function price() external view returns (int256) { (, int256 answer, , uint256 updatedAt, ) = feed.latestRoundData(); require(answer > 0); return answer; } }",
+                    "contract A { function price() external view returns (int256) { (, int256 answer, , uint256 updatedAt, ) = feed.latestRoundData(); require(answer > 0 && updatedAt != 0); return answer; } }",
+                ]),
+                should_not_match: snippets(&[
+                    "contract A { function price() external view returns (int256) { (, int256 answer, , uint256 updatedAt, ) = feed.latestRoundData(); require(block.timestamp - updatedAt <= heartbeat); return answer; } }",
+                    "contract A { function price() external view returns (int256) { (, int256 answer, , uint256 updatedAt, ) = feed.latestRoundData(); uint256 age = block.timestamp - updatedAt; if (age > heartbeat) revert Stale(); return answer; } }",
+                    "contract A { function price() external view returns (int256) { (, int256 answer, , uint256 updatedAt, ) = feed.latestRoundData(); _checkFreshness(updatedAt); return answer; } }",
+                ]),
+                ..rule(
+                    "M-02",
+                    Severity::Medium,
+                    "Chainlink `latestRoundData()` result is not checked for staleness",
+                    "`updatedAt` is discarded or never compared with the current time, so a price that stopped being updated (feed deprecated, oracle down, heartbeat missed) is used as if it were current.",
+                    "Check that `block.timestamp - updatedAt` does not exceed the heartbeat of the feed, and revert (or fall back to another source) otherwise.",
+                    &["chainlink", "oracle"],
+                )
+            },
+        }
+    }
+}
+
+impl Detector for StalePrice {
+    fn rule(&self) -> &Rule {
+        &self.rule
+    }
+
+    fn detect(&self, _source: &str, unit: &SourceUnit) -> Vec<Detection> {
+        consumers(unit).iter()
+            .filter(|consumer| consumer.timestamp_comparisons(UPDATED_AT).is_empty() && !consumer.is_validated(UPDATED_AT))
+            .map(|consumer| consumer.detection(UPDATED_AT, "is never checked"))
+            .collect()
+    }
+}
+
+// M-03: `answer` of `latestRoundData()` never checked to be positive
+pub struct UncheckedAnswer {
+    rule: Rule,
+}
+
+impl UncheckedAnswer {
+    pub fn new() -> UncheckedAnswer {
+        UncheckedAnswer {
+            rule: Rule {
+                should_match: snippets(&[
+                    "contract A { function price() external view returns (uint256) { (, int256 answer, , uint256 updatedAt, ) = feed.latestRoundData(); require(block.timestamp - updatedAt <= heartbeat); return uint256(answer); } }",
+                    "contract A { function price() external view returns (uint256) { (, int256 answer, , uint256 updatedAt, ) = feed.latestRoundData(); require(block.timestamp - updatedAt <= heartbeat); require(uint256(answer) <= maxPrice); return uint256(answer); } }",
+                ]),
+                should_not_match: snippets(&[
+                    "contract A { function price() external view returns (uint256) { (, int256 answer, , uint256 updatedAt, ) = feed.latestRoundData(); require(answer > 0, \"invalid price\"); require(block.timestamp - updatedAt <= heartbeat); return uint256(answer); } }",
+                    "contract A { function price() external view returns (uint256) { try feed.latestRoundData() returns (uint80, int256 answer, uint256, uint256, uint80) { if (answer <= 0) revert InvalidPrice(); return uint256(answer); } catch { return fallbackPrice; } } }",
+                    "contract A { function price() external view returns (uint256) { (, int256 answer, , uint256 updatedAt, ) = feed.latestRoundData(); require(block.timestamp - updatedAt <= heartbeat && minAnswer <= answer); return uint256(answer); } }",
+                ]),
+                ..rule(
+                    "M-03",
+                    Severity::Medium,
+                    "Chainlink `latestRoundData()` answer is not checked to be positive",
+                    "`answer` is a signed integer that is used without checking it against a lower bound, so a zero or negative price (e.g. when the feed hits its minimum answer or is misconfigured) is used, or wraps around when cast to `uint256`.",
+                    "Require `answer > 0` (or above a configured minimum) before using it.",
+                    &["chainlink", "oracle"],
+                )
+            },
+        }
+    }
+}
+
+impl Detector for UncheckedAnswer {
+    fn rule(&self) -> &Rule {
+        &self.rule
+    }
+
+    fn detect(&self, _source: &str, unit: &SourceUnit) -> Vec<Detection> {
+        consumers(unit).iter()
+            .filter(|consumer| !consumer.is_bounded_below(ANSWER) && !consumer.is_validated(ANSWER))
+            .map(|consumer| consumer.detection(ANSWER, "is never checked"))
+            .collect()
+    }
+}
+
+// L-09: `answeredInRound` of `latestRoundData()` stored but never compared with `roundId`
+pub struct IgnoredAnsweredInRound {
+    rule: Rule,
+}
+
+impl IgnoredAnsweredInRound {
+    pub fn new() -> IgnoredAnsweredInRound {
+        IgnoredAnsweredInRound {
+            rule: Rule {
+                should_match: snippets(&[
+                    "contract A { function price() external view returns (int256) { (uint80 roundId, int256 answer, , , uint80 answeredInRound) = feed.latestRoundData(); require(answer > 0); return answer; } }",
+                    "contract A { function price() external view returns (int256) { (uint80 roundId, int256 answer, , uint256 updatedAt, uint80 answeredInRound) = feed.latestRoundData(); require(answer > 0 && block.timestamp - updatedAt <= heartbeat); require(answeredInRound != 0); return answer; } }",
+                ]),
+                should_not_match: snippets(&[
+                    "contract A { function price() external view returns (int256) { (uint80 roundId, int256 answer, , , uint80 answeredInRound) = feed.latestRoundData(); require(answeredInRound >= roundId, \"stale round\"); require(answer > 0); return answer; } }",
+                    "contract A { function price() external view returns (int256) { (, int256 answer, , uint256 updatedAt, ) = feed.latestRoundData(); require(answer > 0 && block.timestamp - updatedAt <= heartbeat); return answer; } }",
+                ]),
+                ..rule(
+                    "L-09",
+                    Severity::Low,
+                    "Chainlink `latestRoundData()` round completeness is not checked",
+                    "`answeredInRound` is stored but never compared with `roundId`, so the check it was read for is missing and an answer carried over from a previous round is not detected.",
+                    "Require `answeredInRound >= roundId`, or stop reading `answeredInRound`: it is deprecated on current aggregators, where a staleness check on `updatedAt` (see M-02) is what detects an old answer.",
+                    &["chainlink", "oracle"],
+                )
+            },
+        }
+    }
+}
+
+impl Detector for IgnoredAnsweredInRound {
+    fn rule(&self) -> &Rule {
+        &self.rule
+    }
+
+    fn detect(&self, _source: &str, unit: &SourceUnit) -> Vec<Detection> {
+        consumers(unit).iter()
+            .filter(|consumer| consumer.result(ANSWERED_IN_ROUND).is_some())
+            .filter(|consumer| !consumer.is_compared(ANSWERED_IN_ROUND, ROUND_ID) && !consumer.is_validated(ANSWERED_IN_ROUND))
+            .map(|consumer| consumer.detection(ANSWERED_IN_ROUND, "is never compared with the round id"))
+            .collect()
+    }
+}
+
+// M-04: `latestRoundData()` used without an L2 sequencer uptime feed
+pub struct MissingSequencerCheck {
+    rule: Rule,
+}
+
+impl MissingSequencerCheck {
+    pub fn new() -> MissingSequencerCheck {
+        MissingSequencerCheck {
+            rule: Rule {
+                should_match: snippets(&["contract A { function price() external view returns (int256 answer) { (, answer, , , ) = feed.latestRoundData(); } }"]),
+                should_not_match: snippets(&["contract A { AggregatorV3Interface sequencerUptimeFeed; function price() external view returns (int256 answer) { (, int256 down, , , ) = sequencerUptimeFeed.latestRoundData(); require(down == 0); (, answer, , , ) = feed.latestRoundData(); } }"]),
+                ..rule(
+                    "M-04",
+                    Severity::Medium,
+                    "Chainlink price feed used without checking the L2 sequencer uptime feed",
+                    "On L2s such as Arbitrum and Optimism, prices stop being updated while the sequencer is down, yet still look valid to contracts once it is back up, and only privileged users can transact during the outage. Nothing in this file refers to a sequencer uptime feed. This only applies to contracts deployed on an L2.",
+                    "On L2 deployments, read the Chainlink sequencer uptime feed and revert while the sequencer is down and for a grace period after it comes back up.",
+                    &["chainlink", "oracle", "l2"],
+                )
+            },
+        }
+    }
+}
+
+impl Detector for MissingSequencerCheck {
+    fn rule(&self) -> &Rule {
+        &self.rule
+    }
+
+    fn detect(&self, _source: &str, unit: &SourceUnit) -> Vec<Detection> {
+        let consumers = consumers(unit);
+        if consumers.is_empty() {
+            return vec![];
+        }
+        let mut names = Names::default();
+        walk_source_unit(&mut names, unit);
+        if names.names.iter().any(|name| name.to_lowercase().contains("sequencer")) {
+            return vec![];
+        }
+        consumers.iter().map(|consumer| consumer.call.into()).collect()
+    }
+}
+
+// L-10: staleness checks against a hardcoded heartbeat
+pub struct HardcodedHeartbeat {
+    rule: Rule,
+}
+
+impl HardcodedHeartbeat {
+    pub fn new() -> HardcodedHeartbeat {
+        HardcodedHeartbeat {
+            rule: Rule {
+                should_match: snippets(&[
+                    "contract A { function price() external view returns (int256) { (, int256 answer, , uint256 updatedAt, ) = feed.latestRoundData(); require(block.timestamp - updatedAt < 3600); return answer; } }",
+                    "contract A { function price() external view returns (int256) { (, int256 answer, , uint256 updatedAt, ) = feed.latestRoundData(); uint256 age = block.timestamp - updatedAt; if (age > 1 hours) revert Stale(); return answer; } }",
+                ]),
+                should_not_match: snippets(&[
+                    "contract A { function price() external view returns (int256) { (, int256 answer, , uint256 updatedAt, ) = feed.latestRoundData(); require(updatedAt != 0 && block.timestamp - updatedAt <= heartbeats[feed]); return answer; } }",
+                ]),
+                ..rule(
+                    "L-10",
+                    Severity::Low,
+                    "Chainlink staleness check uses a hardcoded heartbeat",
+                    "Every feed has its own heartbeat (from 1 hour to 24 hours, and it differs between chains for the same pair). A hardcoded limit is either too loose for fast feeds, accepting stale prices, or too strict for slow ones, reverting on valid prices.",
+                    "Configure the heartbeat per feed, e.g. in a mapping set when the feed is added, instead of a literal.",
+                    &["chainlink", "oracle"],
+                )
+            },
+        }
+    }
+}
+
+impl Detector for HardcodedHeartbeat {
+    fn rule(&self) -> &Rule {
+        &self.rule
+    }
+
+    fn detect(&self, _source: &str, unit: &SourceUnit) -> Vec<Detection> {
+        let mut detections = vec![];
+        for consumer in consumers(unit) {
+            let literals: Vec<Span> = consumer.timestamp_comparisons(UPDATED_AT).into_iter().flat_map(|(left, right)| number_literals(left).into_iter().chain(number_literals(right))).collect();
+            if !literals.is_empty() {
+                detections.push(Detection { span: consumer.call, related: literals.into_iter().map(|span| (span, "hardcoded heartbeat".to_string())).collect() });
+            }
+        }
+        detections
+    }
+}
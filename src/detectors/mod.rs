@@ -0,0 +1,359 @@
+use crate::config::Config;
+use crate::regexrules::{Rule, Severity};
+use crate::solidity::ast::{walk_block, walk_expression, walk_statement, Block, Contract, Expression, ExpressionKind, Function, SourceUnit, Span, Statement, StatementKind, Visitor};
+
+mod access;
+mod calldata;
+mod calls;
+mod chainlink;
+mod modifiers;
+mod reentrancy;
+mod upgrades;
+
+// Modifiers restricting who calls a function, besides the `only...` ones
+static ACCESS_MODIFIERS: [&str; 3] = ["auth", "requiresAuth", "restricted"];
+
+// Words in the names of internal functions checking the caller, e.g. `_checkOwner()`, `_checkRole(role)`
+static CHECK_PREFIXES: [&str; 6] = ["check", "only", "require", "ensure", "assert", "verify"];
+static CHECK_WORDS: [&str; 4] = ["owner", "role", "auth", "admin"];
+
+// Words in the names of mappings listing privileged accounts, e.g. `roles[ADMIN][msg.sender]`, `whitelisted[msg.sender]`
+static ALLOWLIST_WORDS: [&str; 13] = [
+    "role", "admin", "auth", "authorized", "wards", "whitelist", "whitelisted", "allowlist", "allowlisted", "operator", "minter", "keeper", "guardian",
+];
+
+// A rule written against the AST instead of the text of a file
+// detectors report through the same rule metadata as regex rules
+pub trait Detector: Send + Sync {
+    // metadata of the findings, `regex` is left empty
+    fn rule(&self) -> &Rule;
+
+    // returns every finding in a parsed file
+    fn detect(&self, source: &str, unit: &SourceUnit) -> Vec<Detection>;
+}
+
+// A finding of a detector: where it is reported, and other places of the file
+// that explain it, each with what it is
+pub struct Detection {
+    pub span: Span,
+    pub related: Vec<(Span, String)>,
+}
+
+impl From<Span> for Detection {
+    fn from(span: Span) -> Detection {
+        Detection { span, related: vec![] }
+    }
+}
+
+// A detector with the rule its findings are reported under, as configured for the project
+pub type ConfiguredDetector = (Rule, Box<dyn Detector>);
+
+// returns the built-in detectors, set up with the project configuration
+pub fn all(config: &Config) -> Vec<Box<dyn Detector>> {
+    vec![
+        Box::new(calldata::MemoryParameters::new()),
+        Box::new(modifiers::PayableOnlyFunctions::new()),
+        Box::new(modifiers::NonReentrantOrder::new()),
+        Box::new(reentrancy::Reentrancy::new()),
+        Box::new(access::MissingAccessControl::new(&config.access_modifiers)),
+        Box::new(calls::UncheckedCalls::new()),
+        Box::new(chainlink::StalePrice::new()),
+        Box::new(chainlink::UncheckedAnswer::new()),
+        Box::new(chainlink::MissingSequencerCheck::new()),
+        Box::new(chainlink::IgnoredAnsweredInRound::new()),
+        Box::new(chainlink::HardcodedHeartbeat::new()),
+        Box::new(upgrades::UnprotectedInitializer::new(&config.access_modifiers)),
+        Box::new(upgrades::UnprotectedUpgrade::new(&config.access_modifiers)),
+        Box::new(upgrades::ConstructorState::new()),
+        Box::new(upgrades::InitializersNotDisabled::new()),
+        Box::new(upgrades::NonUpgradeableBase::new()),
+        Box::new(upgrades::MissingStorageGap::new()),
+    ]
+}
+
+// builds the metadata of a detector
+fn rule(id: &str, severity: Severity, title: &str, description: &str, recommendation: &str, tags: &[&str]) -> Rule {
+    Rule {
+        id: id.to_string(),
+        title: title.to_string(),
+        description: description.to_string(),
+        recommendation: recommendation.to_string(),
+        severity,
+        tags: tags.iter().map(|tag| tag.to_string()).collect(),
+        ..Rule::default()
+    }
+}
+
+// builds the `should_match`/`should_not_match` snippets of a detector
+fn snippets(snippets: &[&str]) -> Vec<String> {
+    snippets.iter().map(|snippet| snippet.to_string()).collect()
+}
+
+// Collects the variables assigned, incremented, deleted, pushed to or popped
+// from in a function body, with the span of each write, in source order
+#[derive(Default)]
+struct Writes {
+    targets: Vec<(String, Span)>,
+}
+
+impl Writes {
+    // returns true if the variable is written
+    fn writes(&self, name: &str) -> bool {
+        self.targets.iter().any(|(target, _)| target == name)
+    }
+}
+
+impl Visitor for Writes {
+    fn visit_expression(&mut self, expression: &Expression) {
+        let target = match &expression.kind {
+            ExpressionKind::Assign(_, target, _) => Some(target),
+            ExpressionKind::Unary(operator, target) if matches!(operator.as_str(), "++" | "--" | "delete") => Some(target),
+            // `values.push(x)`, `values.pop()`
+            ExpressionKind::Call(callee, _) if matches!(callee.member_name(), Some("push" | "pop")) => match &callee.kind {
+                ExpressionKind::Member(base, _) => Some(base),
+                _ => None,
+            },
+            _ => None,
+        };
+        if let Some(target) = target {
+            let span = expression.span;
+            match &target.kind {
+                // `(a, b) = ...`
+                ExpressionKind::Tuple(elements) => self.targets.extend(elements.iter().flatten().filter_map(|element| element.root_identifier()).map(|name| (name.to_string(), span))),
+                _ => self.targets.extend(target.root_identifier().map(|name| (name.to_string(), span))),
+            }
+        }
+        walk_expression(self, expression);
+    }
+}
+
+// Collects what a function body tests: `require`/`assert` arguments and
+// `if`/`while`/ternary conditions, and apart from them the returned values
+// `reverting` are the `if` conditions under which the function reverts, also in `tested`
+#[derive(Default)]
+struct Conditions {
+    tested: Vec<Expression>,
+    returned: Vec<Expression>,
+    reverting: Vec<Expression>,
+}
+
+impl Visitor for Conditions {
+    fn visit_statement(&mut self, statement: &Statement) {
+        match &statement.kind {
+            StatementKind::If(condition, then, _) if reverts(then) => {
+                self.tested.push(condition.clone());
+                self.reverting.push(condition.clone());
+            }
+            StatementKind::If(condition, ..) | StatementKind::While(condition, _) | StatementKind::DoWhile(_, condition) => self.tested.push(condition.clone()),
+            StatementKind::Return(Some(value)) => self.returned.push(value.clone()),
+            _ => {}
+        }
+        walk_statement(self, statement);
+    }
+
+    fn visit_expression(&mut self, expression: &Expression) {
+        match &expression.kind {
+            ExpressionKind::Call(callee, arguments) if matches!(&callee.kind, ExpressionKind::Identifier(name) if name == "require" || name == "assert") => {
+                self.tested.extend(arguments.iter().cloned());
+            }
+            ExpressionKind::Conditional(condition, ..) => self.tested.push(condition.as_ref().clone()),
+            _ => {}
+        }
+        walk_expression(self, expression);
+    }
+}
+
+// returns true if a statement reverts right away: `revert Error()`, `revert("...")`, or a block starting with one
+fn reverts(statement: &Statement) -> bool {
+    match &statement.kind {
+        StatementKind::Revert(_) => true,
+        StatementKind::Expression(Expression { kind: ExpressionKind::Call(callee, _), .. }) => matches!(&callee.kind, ExpressionKind::Identifier(name) if name == "revert"),
+        StatementKind::Block(block) => block.statements.first().is_some_and(reverts),
+        _ => false,
+    }
+}
+
+// returns the comparisons (`<`, `<=`, `>`, `>=`) of an expression as (operator, left, right)
+fn comparisons(expression: &Expression) -> Vec<(&str, &Expression, &Expression)> {
+    let mut found = vec![];
+    let mut pending = vec![expression];
+    while let Some(expression) = pending.pop() {
+        match &expression.kind {
+            ExpressionKind::Binary(operator, left, right) => {
+                if matches!(operator.as_str(), "<" | "<=" | ">" | ">=") {
+                    found.push((operator.as_str(), left.as_ref(), right.as_ref()));
+                }
+                pending.push(left);
+                pending.push(right);
+            }
+            ExpressionKind::Unary(_, operand) => pending.push(operand),
+            // `uint256(answer) > 0` and nested conditions in calls such as `_require(a > b)`
+            ExpressionKind::Call(_, arguments) => pending.extend(arguments),
+            _ => {}
+        }
+    }
+    found
+}
+
+// Collects every identifier of an expression with its offset
+#[derive(Default)]
+struct Identifiers {
+    names: Vec<(String, usize)>,
+}
+
+impl Visitor for Identifiers {
+    fn visit_expression(&mut self, expression: &Expression) {
+        if let ExpressionKind::Identifier(name) = &expression.kind {
+            self.names.push((name.clone(), expression.span.start));
+        }
+        walk_expression(self, expression);
+    }
+}
+
+// returns the identifiers of an expression with their offsets, in source order
+fn identifiers(expression: &Expression) -> Vec<(String, usize)> {
+    let mut identifiers = Identifiers::default();
+    identifiers.visit_expression(expression);
+    identifiers.names
+}
+
+// Collects the parameters and local variables of a function, which shadow state variables
+struct Locals {
+    names: Vec<String>,
+}
+
+impl Locals {
+    // returns the parameters, named returns and local variables of a function
+    fn of(function: &Function) -> Locals {
+        let mut locals = Locals { names: function.parameters.iter().chain(&function.returns).filter_map(|parameter| parameter.name.clone()).collect() };
+        if let Some(body) = &function.body {
+            walk_block(&mut locals, body);
+        }
+        locals
+    }
+}
+
+impl Visitor for Locals {
+    fn visit_statement(&mut self, statement: &Statement) {
+        if let StatementKind::VariableDeclaration(variables, _) = &statement.kind {
+            self.names.extend(variables.iter().flatten().filter_map(|variable| variable.name.clone()));
+        }
+        walk_statement(self, statement);
+    }
+}
+
+// returns true if only some callers can run a function: it has an `only...`, a built-in or
+// configured access control modifier, or a modifier of its contract checking the caller,
+// or its body checks the caller
+fn is_restricted(function: &Function, contract: &Contract, configured: &[String]) -> bool {
+    let checking = |name: &str| contract.modifiers.iter().any(|modifier| modifier.name.as_deref() == Some(name) && modifier.body.as_ref().is_some_and(checks_caller));
+    function.modifiers.iter().any(|modifier| {
+        let name = modifier.name.rsplit('.').next().unwrap_or_default();
+        (name.len() > 4 && name.starts_with("only")) || ACCESS_MODIFIERS.contains(&name) || configured.iter().any(|configured| configured == name) || checking(name)
+    }) || function.body.as_ref().is_some_and(checks_caller)
+}
+
+// returns true if an expression is `msg.sender`, `_msgSender()` or `tx.origin`
+fn is_caller(expression: &Expression) -> bool {
+    match &expression.kind {
+        ExpressionKind::Member(base, member) => matches!((&base.kind, member.as_str()), (ExpressionKind::Identifier(base), "sender") if base == "msg")
+            || matches!((&base.kind, member.as_str()), (ExpressionKind::Identifier(base), "origin") if base == "tx"),
+        ExpressionKind::Call(callee, arguments) => arguments.is_empty() && matches!(&callee.kind, ExpressionKind::Identifier(name) if name == "_msgSender"),
+        _ => false,
+    }
+}
+
+// returns true if a block tests the caller in a condition, or calls an internal check such as `_checkOwner()`
+fn checks_caller(body: &Block) -> bool {
+    let mut conditions = Conditions::default();
+    walk_block(&mut conditions, body);
+    let mut callers = Callers::default();
+    for condition in &conditions.tested {
+        callers.visit_expression(condition);
+    }
+    let mut checks = Checks::default();
+    walk_block(&mut checks, body);
+    callers.found || checks.found
+}
+
+// Finds the caller (`msg.sender`, `_msgSender()` or `tx.origin`) checked in an expression:
+// compared with `==`/`!=`, looked up in an allowlist mapping, or passed to a `has...`/`is...` function
+// such as `hasRole(role, msg.sender)`; `balances[msg.sender] > 0` does not check it
+#[derive(Default)]
+struct Callers {
+    found: bool,
+}
+
+impl Visitor for Callers {
+    fn visit_expression(&mut self, expression: &Expression) {
+        self.found |= match &expression.kind {
+            ExpressionKind::Binary(operator, left, right) => matches!(operator.as_str(), "==" | "!=") && (is_caller(left) || is_caller(right)),
+            ExpressionKind::Index(base, Some(index)) => is_caller(index) && base.root_identifier().is_some_and(|name| {
+                words(name).iter().any(|word| ALLOWLIST_WORDS.iter().any(|allowlist| word == allowlist || word.strip_suffix('s') == Some(allowlist)))
+            }),
+            ExpressionKind::Call(callee, arguments) if arguments.iter().any(is_caller) => {
+                let name = match &callee.kind {
+                    ExpressionKind::Identifier(name) | ExpressionKind::Member(_, name) => name.trim_start_matches('_'),
+                    _ => "",
+                };
+                ["has", "is"].iter().any(|prefix| name.strip_prefix(prefix).is_some_and(|rest| rest.starts_with(char::is_uppercase)))
+            }
+            _ => false,
+        };
+        walk_expression(self, expression);
+    }
+}
+
+// Finds calls to internal functions checking the caller, e.g. `_checkOwner()`, `_checkRole(ADMIN_ROLE)`
+#[derive(Default)]
+struct Checks {
+    found: bool,
+}
+
+impl Visitor for Checks {
+    fn visit_expression(&mut self, expression: &Expression) {
+        if let ExpressionKind::Call(callee, _) = &expression.kind {
+            if let ExpressionKind::Identifier(name) = &callee.kind {
+                let name = name.trim_start_matches('_').to_lowercase();
+                self.found |= CHECK_PREFIXES.iter().any(|prefix| name.starts_with(prefix)) && CHECK_WORDS.iter().any(|word| name.contains(word));
+            }
+        }
+        walk_expression(self, expression);
+    }
+}
+
+// splits a camelCase or snake_case name into lowercase words,
+// e.g. `protocolFeeBps` into `protocol`, `fee`, `bps` and `PRICE_FEED` into `price`, `feed`
+fn words(name: &str) -> Vec<String> {
+    let chars: Vec<char> = name.chars().collect();
+    let mut words = vec![];
+    let mut word = String::new();
+    for (index, c) in chars.iter().enumerate() {
+        let previous = index.checked_sub(1).map(|index| chars[index]);
+        let next = chars.get(index + 1);
+        // `feeRate`, and `ERC20Owner` before the `O` followed by a lowercase letter
+        let boundary = c.is_uppercase() && previous.is_some_and(|previous| previous.is_lowercase() || previous.is_ascii_digit() || (previous.is_uppercase() && next.is_some_and(|next| next.is_lowercase())));
+        if (*c == '_' || boundary) && !word.is_empty() {
+            words.push(std::mem::take(&mut word));
+        }
+        if *c != '_' {
+            word.extend(c.to_lowercase());
+        }
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+    words
+}
+
+// returns true for the names of initialization functions:
+// `initialize`, `initializeV2`, `reinitialize` and `init`
+fn is_initialization_name(name: &str) -> bool {
+    let versioned = name.strip_prefix("initializeV").is_some_and(|version| !version.is_empty() && version.chars().all(|c| c.is_ascii_digit()));
+    matches!(name, "initialize" | "reinitialize" | "init") || versioned
+}
+
+// returns every function of a file with a body or not, file-level ones included
+fn functions(unit: &SourceUnit) -> impl Iterator<Item = &Function> {
+    unit.contracts.iter().flat_map(|contract| &contract.functions).chain(&unit.functions)
+}
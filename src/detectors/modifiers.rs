@@ -0,0 +1,84 @@
+use crate::regexrules::{Rule, Severity};
+use crate::solidity::ast::*;
+use super::{functions, rule, snippets, Detection, Detector};
+
+// G-14: non-payable functions restricted by an `only...` modifier
+pub struct PayableOnlyFunctions {
+    rule: Rule,
+}
+
+impl PayableOnlyFunctions {
+    pub fn new() -> PayableOnlyFunctions {
+        PayableOnlyFunctions {
+            rule: Rule {
+                should_match: snippets(&["contract A { function pause() external onlyOwner { paused = true; } }"]),
+                should_not_match: snippets(&[
+                    "contract A { function pause() external payable onlyOwner { paused = true; } }",
+                    "contract A { function pause() external whenNotPaused { paused = true; } }",
+                ]),
+                ..rule(
+                    "G-14",
+                    Severity::Gas,
+                    "Functions guaranteed to revert when called by normal users can be marked `payable`",
+                    "If a function modifier such as `onlyOwner` is used, the function will revert if a normal user tries to pay the function. Marking the function as `payable` will lower the gas cost for legitimate callers because the compiler will not include checks for whether a payment was provided.",
+                    "Consider marking above functions as payable",
+                    &["modifiers"],
+                )
+            },
+        }
+    }
+}
+
+impl Detector for PayableOnlyFunctions {
+    fn rule(&self) -> &Rule {
+        &self.rule
+    }
+
+    fn detect(&self, _source: &str, unit: &SourceUnit) -> Vec<Detection> {
+        functions(unit)
+            .filter(|function| function.kind == FunctionKind::Function && function.is_external() && function.mutability.is_none())
+            .filter(|function| function.modifiers.iter().any(|modifier| {
+                let name = modifier.name.rsplit('.').next().unwrap_or_default();
+                name.len() > 4 && name.starts_with("only")
+            }))
+            .map(|function| function.header.into())
+            .collect()
+    }
+}
+
+// N-05: `nonReentrant` placed after other modifiers
+pub struct NonReentrantOrder {
+    rule: Rule,
+}
+
+impl NonReentrantOrder {
+    pub fn new() -> NonReentrantOrder {
+        NonReentrantOrder {
+            rule: Rule {
+                should_match: snippets(&["contract A { function withdraw() external onlyOwner nonReentrant { send(); } }"]),
+                should_not_match: snippets(&["contract A { function withdraw() external nonReentrant onlyOwner { send(); } }"]),
+                ..rule(
+                    "N-05",
+                    Severity::NonCritical,
+                    "The `nonReentrant` `modifier` should occur before all other modifiers",
+                    "This is a best-practice to protect against reentrancy in other modifiers",
+                    "",
+                    &["modifiers", "reentrancy"],
+                )
+            },
+        }
+    }
+}
+
+impl Detector for NonReentrantOrder {
+    fn rule(&self) -> &Rule {
+        &self.rule
+    }
+
+    fn detect(&self, _source: &str, unit: &SourceUnit) -> Vec<Detection> {
+        functions(unit)
+            .filter(|function| function.modifiers.iter().position(|modifier| modifier.name == "nonReentrant").is_some_and(|index| index > 0))
+            .map(|function| function.header.into())
+            .collect()
+    }
+}
@@ -0,0 +1,112 @@
+use crate::regexrules::{Rule, Severity};
+use crate::solidity::ast::*;
+use super::{rule, snippets, Detection, Detector, Locals, Writes};
+
+// H-01: state variables written after an external call, in functions without `nonReentrant`
+pub struct Reentrancy {
+    rule: Rule,
+}
+
+impl Reentrancy {
+    pub fn new() -> Reentrancy {
+        Reentrancy {
+            rule: Rule {
+                should_match: snippets(&[
+                    "contract Vault { mapping(address => uint256) balances; function withdraw() external { (bool ok, ) = msg.sender.call{value: balances[msg.sender]}(\"\"); require(ok); balances[msg.sender] = 0; } }",
+                    "contract Market { mapping(uint256 => address) owners; function buy(uint256 id) external { nft.safeTransferFrom(address(this), msg.sender, id); owners[id] = msg.sender; } }",
+                    "contract Pool { uint256 total; function deposit(uint256 amount) external { token.transferFrom(msg.sender, address(this), amount); total += amount; } }",
+                ]),
+                should_not_match: snippets(&[
+                    "contract Vault { mapping(address => uint256) balances; function withdraw() external { uint256 amount = balances[msg.sender]; balances[msg.sender] = 0; (bool ok, ) = msg.sender.call{value: amount}(\"\"); require(ok); } }",
+                    "contract Vault { mapping(address => uint256) balances; function withdraw() external nonReentrant { (bool ok, ) = msg.sender.call{value: balances[msg.sender]}(\"\"); require(ok); balances[msg.sender] = 0; } }",
+                    "contract Vault { uint256 total; function pay(address to, uint256 amount) external { payable(to).transfer(amount); total -= amount; } }",
+                    "contract Vault { function pay(address to, uint256 amount) external { uint256 total; token.transfer(to, amount); total = amount; } }",
+                ]),
+                ..rule(
+                    "H-01",
+                    Severity::High,
+                    "Reentrancy: state is written after an external call",
+                    "The function hands control to another contract (low-level `call`, a call sending value, a token transfer, or a `safe*` transfer or mint calling back ERC721/ERC1155 receivers or ERC777 hooks) before updating its own state. The callee can call back into the contract while the state is stale, e.g. to withdraw the same balance twice.",
+                    "Follow the checks-effects-interactions pattern by updating the state before the external call, or add the `nonReentrant` modifier of OpenZeppelin's `ReentrancyGuard` to the function.",
+                    &["reentrancy", "external-calls"],
+                )
+            },
+        }
+    }
+}
+
+impl Detector for Reentrancy {
+    fn rule(&self) -> &Rule {
+        &self.rule
+    }
+
+    fn detect(&self, _source: &str, unit: &SourceUnit) -> Vec<Detection> {
+        let mut detections = vec![];
+        for contract in &unit.contracts {
+            let state: Vec<&str> = contract.state_variables.iter()
+                .filter(|variable| !matches!(variable.mutability, Some(Mutability::Constant | Mutability::Immutable)))
+                .map(|variable| variable.name.as_str())
+                .collect();
+            let guarded = |function: &Function| function.has_modifier("nonReentrant");
+            let read_only = |function: &Function| matches!(function.mutability, Some(Mutability::View | Mutability::Pure));
+            for function in contract.functions.iter().filter(|function| function.is_external() && !read_only(function) && !guarded(function)) {
+                let Some(body) = &function.body else { continue };
+                let mut calls = Calls::default();
+                walk_block(&mut calls, body);
+                if calls.spans.is_empty() {
+                    continue;
+                }
+                let mut writes = Writes::default();
+                walk_block(&mut writes, body);
+                let locals = Locals::of(function);
+
+                for call in calls.spans {
+                    // the first write of a state variable once the call returned
+                    let write = writes.targets.iter()
+                        .find(|(name, span)| span.start >= call.end && state.contains(&name.as_str()) && !locals.names.contains(name));
+                    if let Some((name, span)) = write {
+                        detections.push(Detection { span: call, related: vec![(*span, format!("`{}` written after the call", name))] });
+                    }
+                }
+            }
+        }
+        detections
+    }
+}
+
+// returns true if a call hands control to another contract: low-level calls,
+// calls with options such as `{value: x}`, ERC20/ERC777 transfers, and the
+// `safe*` transfers and mints calling back ERC721/ERC1155 receivers
+fn is_external_call(callee: &Expression, arguments: &[Expression]) -> bool {
+    match &callee.kind {
+        // `new Pool{salt: s}()` only runs a constructor
+        ExpressionKind::CallOptions(target, _) => !matches!(target.kind, ExpressionKind::New),
+        ExpressionKind::Member(_, member) => match member.as_str() {
+            "call" | "safeTransfer" | "safeTransferFrom" | "safeBatchTransferFrom" | "safeMint" | "operatorSend" => true,
+            // `payable(to).transfer(amount)` and `payable(to).send(amount)` only forward 2300 gas
+            "transfer" => arguments.len() == 2,
+            "transferFrom" => arguments.len() == 3,
+            "send" => arguments.len() == 3,
+            _ => false,
+        },
+        ExpressionKind::Identifier(name) => matches!(name.as_str(), "_safeMint" | "_safeTransfer"),
+        _ => false,
+    }
+}
+
+// Collects the external calls of a function body, in source order
+#[derive(Default)]
+struct Calls {
+    spans: Vec<Span>,
+}
+
+impl Visitor for Calls {
+    fn visit_expression(&mut self, expression: &Expression) {
+        if let ExpressionKind::Call(callee, arguments) = &expression.kind {
+            if is_external_call(callee, arguments) {
+                self.spans.push(expression.span);
+            }
+        }
+        walk_expression(self, expression);
+    }
+}
@@ -0,0 +1,381 @@
+use crate::regexrules::{Rule, Severity};
+use crate::solidity::ast::*;
+use super::{identifiers, is_initialization_name, is_restricted, rule, snippets, Conditions, Detection, Detector, Locals, Writes};
+
+// OpenZeppelin contracts with an `...Upgradeable` version to use behind a proxy
+static NON_UPGRADEABLE_BASES: [&str; 20] = [
+    "Ownable", "Ownable2Step", "AccessControl", "AccessControlEnumerable", "Pausable", "ReentrancyGuard",
+    "ERC20", "ERC20Burnable", "ERC20Capped", "ERC20Pausable", "ERC20Permit", "ERC20Votes",
+    "ERC721", "ERC721Enumerable", "ERC721URIStorage", "ERC721Burnable", "ERC1155", "ERC4626",
+    "EIP712", "Multicall",
+];
+
+// returns the last segment of a qualified name, e.g. `Ownable` for `oz.Ownable`
+fn base_name(name: &str) -> &str {
+    name.rsplit('.').next().unwrap_or_default()
+}
+
+// returns true if a function can only run as part of an initialization
+fn is_initializer(function: &Function) -> bool {
+    function.has_modifier("initializer") || function.has_modifier("reinitializer")
+}
+
+// returns the contracts of a file meant to run behind a proxy: inheriting `Initializable` or an
+// `...Upgradeable` contract, directly or through other contracts of the file, or with an initializer
+fn upgradeable(unit: &SourceUnit) -> Vec<&Contract> {
+    let candidates: Vec<&Contract> = unit.contracts.iter()
+        .filter(|contract| matches!(contract.kind, ContractKind::Contract | ContractKind::AbstractContract))
+        .collect();
+    let mut names: Vec<&str> = vec![];
+    let mut grown = true;
+    while grown {
+        grown = false;
+        for contract in &candidates {
+            let upgradeable = contract.bases.iter().map(|base| base_name(&base.name)).any(|base| base == "Initializable" || base.ends_with("Upgradeable") || names.contains(&base))
+                || contract.functions.iter().any(is_initializer);
+            if upgradeable && !names.contains(&contract.name.as_str()) {
+                names.push(&contract.name);
+                grown = true;
+            }
+        }
+    }
+    candidates.into_iter().filter(|contract| names.contains(&contract.name.as_str())).collect()
+}
+
+// returns the state variables of a contract that live in storage
+fn storage_variables(contract: &Contract) -> impl Iterator<Item = &StateVariable> {
+    contract.state_variables.iter().filter(|variable| !matches!(variable.mutability, Some(Mutability::Constant | Mutability::Immutable)))
+}
+
+// M-05: storage set by the constructor of a contract behind a proxy
+pub struct ConstructorState {
+    rule: Rule,
+}
+
+impl ConstructorState {
+    pub fn new() -> ConstructorState {
+        ConstructorState {
+            rule: Rule {
+                should_match: snippets(&[
+                    "contract Vault is Initializable { address public owner; constructor() { owner = msg.sender; } function initialize() external initializer {} }",
+                    "contract Vault is UUPSUpgradeable { uint256 public fee = 100; function initialize() external initializer {} }",
+                ]),
+                should_not_match: snippets(&[
+                    "contract Vault is Initializable { address public immutable token; constructor(address _token) { token = _token; _disableInitializers(); } function initialize() external initializer {} }",
+                    "contract Vault { address public owner; uint256 public fee = 100; constructor() { owner = msg.sender; } }",
+                ]),
+                ..rule(
+                    "M-05",
+                    Severity::Medium,
+                    "Storage set by the constructor of an upgradeable contract",
+                    "The constructor (and the initial values of state variables, which it assigns) only runs on the implementation. A proxy delegating to it uses its own storage, where these variables are never set.",
+                    "Set these variables in the initializer instead, or make them `immutable` or `constant` if they never change.",
+                    &["upgradeability", "proxy"],
+                )
+            },
+        }
+    }
+}
+
+impl Detector for ConstructorState {
+    fn rule(&self) -> &Rule {
+        &self.rule
+    }
+
+    fn detect(&self, _source: &str, unit: &SourceUnit) -> Vec<Detection> {
+        let mut detections = vec![];
+        for contract in upgradeable(unit) {
+            let storage: Vec<&str> = storage_variables(contract).map(|variable| variable.name.as_str()).collect();
+            detections.extend(storage_variables(contract).filter(|variable| variable.value.is_some()).map(|variable| Detection::from(variable.span)));
+            for constructor in contract.functions.iter().filter(|function| function.kind == FunctionKind::Constructor) {
+                let Some(body) = &constructor.body else { continue };
+                let mut writes = Writes::default();
+                walk_block(&mut writes, body);
+                let locals = Locals::of(constructor);
+                let related: Vec<(Span, String)> = writes.targets.iter()
+                    .filter(|(name, _)| storage.contains(&name.as_str()) && !locals.names.contains(name))
+                    .map(|(name, span)| (*span, format!("`{}` written", name)))
+                    .collect();
+                if !related.is_empty() {
+                    detections.push(Detection { span: constructor.header, related });
+                }
+            }
+        }
+        detections.sort_by_key(|detection| detection.span.start);
+        detections
+    }
+}
+
+// M-06: implementation contracts whose initializers are not disabled
+pub struct InitializersNotDisabled {
+    rule: Rule,
+}
+
+impl InitializersNotDisabled {
+    pub fn new() -> InitializersNotDisabled {
+        InitializersNotDisabled {
+            rule: Rule {
+                should_match: snippets(&[
+                    "contract Vault is UUPSUpgradeable { function initialize() external initializer {} }",
+                    "contract Vault is UUPSUpgradeable { constructor() {} function initialize() external initializer {} }",
+                ]),
+                should_not_match: snippets(&[
+                    "contract Vault is UUPSUpgradeable { constructor() { _disableInitializers(); } function initialize() external initializer {} }",
+                    "contract Vault is UUPSUpgradeable { constructor() initializer {} function initialize() external initializer {} }",
+                    "abstract contract Base is Initializable { function __Base_init() internal onlyInitializing {} }",
+                ]),
+                ..rule(
+                    "M-06",
+                    Severity::Medium,
+                    "Initializers of the implementation contract are not disabled",
+                    "Anyone can call the initializer on the implementation contract itself and take it over, e.g. to become its owner, and for UUPS implementations upgrade it to a contract that self-destructs.",
+                    "Call `_disableInitializers()` in the constructor of the implementation contract.",
+                    &["upgradeability", "proxy"],
+                )
+            },
+        }
+    }
+}
+
+impl Detector for InitializersNotDisabled {
+    fn rule(&self) -> &Rule {
+        &self.rule
+    }
+
+    fn detect(&self, _source: &str, unit: &SourceUnit) -> Vec<Detection> {
+        let mut detections = vec![];
+        for contract in upgradeable(unit).into_iter().filter(|contract| contract.kind == ContractKind::Contract) {
+            let Some(initializer) = contract.functions.iter().find(|function| is_initializer(function) && function.kind == FunctionKind::Function) else { continue };
+            match contract.functions.iter().find(|function| function.kind == FunctionKind::Constructor) {
+                // `constructor() initializer {}` locks the implementation as well
+                Some(constructor) if constructor.has_modifier("initializer") || constructor.body.as_ref().is_some_and(disables_initializers) => {}
+                Some(constructor) => detections.push(constructor.header.into()),
+                None => detections.push(initializer.header.into()),
+            }
+        }
+        detections
+    }
+}
+
+// returns true if a block calls `_disableInitializers()`
+fn disables_initializers(body: &Block) -> bool {
+    #[derive(Default)]
+    struct Disables {
+        found: bool,
+    }
+    impl Visitor for Disables {
+        fn visit_expression(&mut self, expression: &Expression) {
+            if let ExpressionKind::Call(callee, _) = &expression.kind {
+                self.found |= matches!(&callee.kind, ExpressionKind::Identifier(name) if name == "_disableInitializers");
+            }
+            walk_expression(self, expression);
+        }
+    }
+    let mut disables = Disables::default();
+    walk_block(&mut disables, body);
+    disables.found
+}
+
+// H-03: `initialize()` functions that can be called again, or by anyone
+pub struct UnprotectedInitializer {
+    rule: Rule,
+    // access control modifiers of the project, from the `access_modifiers` of auditooor.toml
+    modifiers: Vec<String>,
+}
+
+impl UnprotectedInitializer {
+    pub fn new(modifiers: &[String]) -> UnprotectedInitializer {
+        UnprotectedInitializer {
+            rule: Rule {
+                should_match: snippets(&[
+                    "contract Vault is UUPSUpgradeable { address public owner; function initialize(address _owner) external { owner = _owner; } }",
+                    "contract Clone { address public owner; function init(address _owner) public { owner = _owner; } }",
+                ]),
+                should_not_match: snippets(&[
+                    "contract Vault is UUPSUpgradeable { address public owner; function initialize(address _owner) external initializer { owner = _owner; } }",
+                    "contract Vault is UUPSUpgradeable { function initializeV2() external reinitializer(2) {} }",
+                    "contract Clone { bool initialized; address public owner; function init(address _owner) public { require(!initialized); initialized = true; owner = _owner; } }",
+                    "contract Clone { address public owner; function init(address _owner) public { require(msg.sender == factory); owner = _owner; } }",
+                    "contract Vault is UUPSUpgradeable { bool private done; function initialized() external view returns (bool) { return done; } function initializeFees() external view returns (uint256) { return 0; } }",
+                ]),
+                ..rule(
+                    "H-03",
+                    Severity::High,
+                    "Unprotected initializer",
+                    "The initialization function has no `initializer`/`reinitializer` modifier, does not check an initialized flag and does not restrict its caller. Anyone can call it again after deployment, e.g. to replace the owner.",
+                    "Add the `initializer` modifier of OpenZeppelin's `Initializable` (or `reinitializer(n)` for later versions).",
+                    &["upgradeability", "proxy", "access-control"],
+                )
+            },
+            modifiers: modifiers.to_vec(),
+        }
+    }
+}
+
+impl Detector for UnprotectedInitializer {
+    fn rule(&self) -> &Rule {
+        &self.rule
+    }
+
+    fn detect(&self, _source: &str, unit: &SourceUnit) -> Vec<Detection> {
+        let mut detections = vec![];
+        for contract in unit.contracts.iter().filter(|contract| matches!(contract.kind, ContractKind::Contract | ContractKind::AbstractContract)) {
+            let initializers = contract.functions.iter().filter(|function| {
+                let read_only = matches!(function.mutability, Some(Mutability::View | Mutability::Pure));
+                function.kind == FunctionKind::Function && function.is_external() && !read_only && function.name.as_deref().is_some_and(is_initialization_name)
+            });
+            for function in initializers {
+                let Some(body) = &function.body else { continue };
+                if is_initializer(function) || is_restricted(function, contract, &self.modifiers) || tests_initialized_flag(body) {
+                    continue;
+                }
+                detections.push(function.header.into());
+            }
+        }
+        detections
+    }
+}
+
+// returns true if a block tests a variable such as `initialized` or `_isInitialized`
+fn tests_initialized_flag(body: &Block) -> bool {
+    let mut conditions = Conditions::default();
+    walk_block(&mut conditions, body);
+    conditions.tested.iter().flat_map(identifiers).any(|(name, _)| name.to_lowercase().contains("initialized"))
+}
+
+// L-11: upgradeable base contracts without a storage gap
+pub struct MissingStorageGap {
+    rule: Rule,
+}
+
+impl MissingStorageGap {
+    pub fn new() -> MissingStorageGap {
+        MissingStorageGap {
+            rule: Rule {
+                should_match: snippets(&[
+                    "abstract contract FeeManager is Initializable { uint256 public fee; }",
+                    "contract Base is OwnableUpgradeable { uint256 public fee; } contract Vault is Base { function initialize() external initializer {} }",
+                ]),
+                should_not_match: snippets(&[
+                    "abstract contract FeeManager is Initializable { uint256 public fee; uint256[49] private __gap; }",
+                    "contract Vault is UUPSUpgradeable { uint256 public fee; function initialize() external initializer {} }",
+                    "abstract contract FeeManager { uint256 public fee; }",
+                ]),
+                ..rule(
+                    "L-11",
+                    Severity::Low,
+                    "Upgradeable base contract without a storage gap",
+                    "Contracts inheriting this one store their variables right after its own. Adding a variable to it in an upgrade shifts the storage of every child contract and corrupts it.",
+                    "Add a `uint256[50] private __gap;` after the state variables, shrinking it by the number of slots taken whenever a variable is added.",
+                    &["upgradeability", "proxy", "storage"],
+                )
+            },
+        }
+    }
+}
+
+impl Detector for MissingStorageGap {
+    fn rule(&self) -> &Rule {
+        &self.rule
+    }
+
+    fn detect(&self, _source: &str, unit: &SourceUnit) -> Vec<Detection> {
+        let inherited = |contract: &Contract| unit.contracts.iter().any(|child| child.bases.iter().any(|base| base_name(&base.name) == contract.name));
+        upgradeable(unit).into_iter()
+            .filter(|contract| contract.kind == ContractKind::AbstractContract || inherited(contract))
+            .filter(|contract| !contract.state_variables.iter().any(|variable| variable.name.starts_with("__gap")))
+            // the gap goes after the last variable
+            .filter_map(|contract| storage_variables(contract).last())
+            .map(|variable| variable.span.into())
+            .collect()
+    }
+}
+
+// H-04: `_authorizeUpgrade` overrides letting anyone upgrade a UUPS proxy
+pub struct UnprotectedUpgrade {
+    rule: Rule,
+    // access control modifiers of the project, from the `access_modifiers` of auditooor.toml
+    modifiers: Vec<String>,
+}
+
+impl UnprotectedUpgrade {
+    pub fn new(modifiers: &[String]) -> UnprotectedUpgrade {
+        UnprotectedUpgrade {
+            rule: Rule {
+                should_match: snippets(&["contract Vault is UUPSUpgradeable { function _authorizeUpgrade(address) internal override {} }"]),
+                should_not_match: snippets(&[
+                    "contract Vault is UUPSUpgradeable, OwnableUpgradeable { function _authorizeUpgrade(address) internal override onlyOwner {} }",
+                    "contract Vault is UUPSUpgradeable { function _authorizeUpgrade(address) internal override { _checkRole(UPGRADER_ROLE); } }",
+                ]),
+                ..rule(
+                    "H-04",
+                    Severity::High,
+                    "Anyone can upgrade the UUPS proxy",
+                    "`_authorizeUpgrade` is what restricts `upgradeToAndCall` in UUPS proxies. Without an access control modifier or a check of `msg.sender`, anyone can upgrade the proxy to their own implementation and take over its funds.",
+                    "Restrict `_authorizeUpgrade` with an access control modifier such as `onlyOwner` or `onlyRole(UPGRADER_ROLE)`.",
+                    &["upgradeability", "proxy", "access-control"],
+                )
+            },
+            modifiers: modifiers.to_vec(),
+        }
+    }
+}
+
+impl Detector for UnprotectedUpgrade {
+    fn rule(&self) -> &Rule {
+        &self.rule
+    }
+
+    fn detect(&self, _source: &str, unit: &SourceUnit) -> Vec<Detection> {
+        let mut detections = vec![];
+        for contract in &unit.contracts {
+            for function in contract.functions.iter().filter(|function| function.name.as_deref() == Some("_authorizeUpgrade") && function.body.is_some()) {
+                if !is_restricted(function, contract, &self.modifiers) {
+                    detections.push(function.header.into());
+                }
+            }
+        }
+        detections
+    }
+}
+
+// M-07: non-upgradeable OpenZeppelin contracts inherited by upgradeable ones
+pub struct NonUpgradeableBase {
+    rule: Rule,
+}
+
+impl NonUpgradeableBase {
+    pub fn new() -> NonUpgradeableBase {
+        NonUpgradeableBase {
+            rule: Rule {
+                should_match: snippets(&["contract Vault is Initializable, Ownable, ERC20 { function initialize() external initializer {} }"]),
+                should_not_match: snippets(&[
+                    "contract Vault is Initializable, OwnableUpgradeable, ERC20Upgradeable { function initialize() external initializer {} }",
+                    "contract Token is Ownable, ERC20 { constructor() ERC20(\"Token\", \"TKN\") {} }",
+                ]),
+                ..rule(
+                    "M-07",
+                    Severity::Medium,
+                    "Upgradeable contract inherits a non-upgradeable OpenZeppelin contract",
+                    "The non-upgradeable OpenZeppelin contracts set their state (owner, name, symbol, domain separator...) in their constructor, which never runs for the proxy, and have no storage gaps.",
+                    "Inherit the `...Upgradeable` version from `@openzeppelin/contracts-upgradeable` and call its `__X_init` function in the initializer.",
+                    &["upgradeability", "proxy"],
+                )
+            },
+        }
+    }
+}
+
+impl Detector for NonUpgradeableBase {
+    fn rule(&self) -> &Rule {
+        &self.rule
+    }
+
+    fn detect(&self, _source: &str, unit: &SourceUnit) -> Vec<Detection> {
+        upgradeable(unit).into_iter()
+            .flat_map(|contract| &contract.bases)
+            .filter(|base| NON_UPGRADEABLE_BASES.contains(&base_name(&base.name)))
+            .map(|base| base.span.into())
+            .collect()
+    }
+}
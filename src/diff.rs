@@ -0,0 +1,187 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::io::Read;
+use std::process::Command;
+
+// Lines added or modified by a diff, per file
+// a deletion marks the line following it, so findings next to removed code are kept
+#[derive(Debug, Default)]
+pub struct ChangedLines {
+    // inclusive line ranges of the new version, keyed by path with `/` separators
+    files: HashMap<String, Vec<(usize, usize)>>,
+    // also keep findings of functions and modifiers with a changed line
+    pub whole_functions: bool,
+}
+
+impl ChangedLines {
+    // reads the changes of the working tree against `base_ref` with `git diff`,
+    // or a unified diff from the standard input when `base_ref` is `-`
+    pub fn load(base_ref: &str, whole_functions: bool) -> Result<ChangedLines, String> {
+        let diff = ChangedLines::read_diff(base_ref)?;
+        Ok(ChangedLines { whole_functions, ..ChangedLines::parse(&diff) })
+    }
+
+    fn read_diff(base_ref: &str) -> Result<String, String> {
+        if base_ref == "-" {
+            let mut diff = String::new();
+            std::io::stdin().read_to_string(&mut diff).map_err(|err| format!("(diff) - Failed to read the diff from stdin: {}", err))?;
+            return Ok(diff);
+        }
+        let output = Command::new("git")
+            .args(["diff", "-U0", "--no-color", "--no-ext-diff", "--relative", base_ref, "--"])
+            .output()
+            .map_err(|err| format!("(diff) - Failed to run git: {}", err))?;
+        if !output.status.success() {
+            return Err(format!("(diff) - git diff {} failed: {}", base_ref, String::from_utf8_lossy(&output.stderr).trim()));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    // parses a unified diff, with or without context lines
+    pub fn parse(diff: &str) -> ChangedLines {
+        let mut files: HashMap<String, Vec<(usize, usize)>> = HashMap::new();
+        let mut current: Option<String> = None;
+        // line number in the new version of the next line, old and new lines left in the hunk
+        let mut new_line = 0;
+        let (mut old_left, mut new_left) = (0, 0);
+        for line in diff.lines() {
+            if old_left == 0 && new_left == 0 {
+                if let Some(path) = line.strip_prefix("+++ ") {
+                    let path = path.split('\t').next().unwrap_or_default().trim();
+                    current = (path != "/dev/null").then(|| path.strip_prefix("b/").unwrap_or(path).to_string());
+                } else if let Some(hunk) = line.strip_prefix("@@ ") {
+                    // `@@ -old_start,old_count +new_start,new_count @@`
+                    let mut ranges = hunk.split_whitespace().take(2).map(|range| {
+                        let mut numbers = range[1..].split(',').map(|number| number.parse::<usize>().unwrap_or(0));
+                        (numbers.next().unwrap_or(0), numbers.next().unwrap_or(1))
+                    });
+                    let (_, old_count) = ranges.next().unwrap_or_default();
+                    let (new_start, new_count) = ranges.next().unwrap_or_default();
+                    // a hunk without new lines, `+N,0`, starts after line N: its deletions mark line N + 1
+                    let new_line_start = if new_count == 0 { new_start + 1 } else { new_start };
+                    (new_line, old_left, new_left) = (new_line_start, old_count, new_count);
+                }
+                continue;
+            }
+            let Some(path) = &current else {
+                (old_left, new_left) = (0, 0);
+                continue;
+            };
+            match line.as_bytes().first() {
+                Some(b'+') => {
+                    push_line(files.entry(path.clone()).or_default(), new_line);
+                    new_line += 1;
+                    new_left = new_left.saturating_sub(1);
+                }
+                Some(b'-') => {
+                    push_line(files.entry(path.clone()).or_default(), new_line.max(1));
+                    old_left = old_left.saturating_sub(1);
+                }
+                Some(b'\\') => {} // `\ No newline at end of file`
+                _ => {
+                    new_line += 1;
+                    old_left = old_left.saturating_sub(1);
+                    new_left = new_left.saturating_sub(1);
+                }
+            }
+        }
+        ChangedLines { files, whole_functions: false }
+    }
+
+    // returns the changed line ranges of a scanned file, None if it did not change
+    // diff paths are relative to the repository or working directory, so either path may be a suffix of the other:
+    // an exact match wins, then the path sharing the longest suffix, then the shortest path
+    pub fn ranges(&self, file: &str) -> Option<&[(usize, usize)]> {
+        let file = file.replace('\\', "/");
+        let file = file.trim_start_matches("./");
+        if let Some(ranges) = self.files.get(file) {
+            return Some(ranges.as_slice());
+        }
+        self.files.iter()
+            .filter(|(path, _)| path.ends_with(&format!("/{}", file)) || file.ends_with(&format!("/{}", path)))
+            .max_by_key(|(path, _)| (path.len().min(file.len()), Reverse(path.len()), Reverse(path.as_str())))
+            .map(|(_, ranges)| ranges.as_slice())
+    }
+}
+
+// adds a line to the ranges of a file, extending the last range when it is adjacent
+fn push_line(ranges: &mut Vec<(usize, usize)>, line: usize) {
+    match ranges.last_mut() {
+        Some((start, end)) if *start <= line && line <= *end + 1 => *end = (*end).max(line),
+        _ => ranges.push((line, line)),
+    }
+}
+
+// returns true if the lines `start..=end` intersect one of the ranges
+pub fn touches(ranges: &[(usize, usize)], start: usize, end: usize) -> bool {
+    ranges.iter().any(|(range_start, range_end)| start <= *range_end && *range_start <= end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // `git diff -U0` after deleting the `require` of `withdraw()`: the line now in its place is changed
+    #[test]
+    fn deleted_require_marks_the_following_line() {
+        let diff = [
+            "diff --git a/src/Vault.sol b/src/Vault.sol",
+            "--- a/src/Vault.sol",
+            "+++ b/src/Vault.sol",
+            "@@ -5 +4,0 @@ contract Vault {",
+            "-        require(balances[msg.sender] >= amount);",
+        ].join("\n");
+        let changes = ChangedLines::parse(&diff);
+        let ranges = changes.ranges("src/Vault.sol").expect("the file changed");
+        assert_eq!(ranges, &[(5, 5)]);
+        assert!(!touches(ranges, 4, 4));
+    }
+
+    // with context lines, the deletion marks the context line that follows it
+    #[test]
+    fn deleted_require_with_context() {
+        let diff = [
+            "--- a/Vault.sol",
+            "+++ b/Vault.sol",
+            "@@ -4,3 +4,2 @@",
+            "     function withdraw(uint256 amount) external {",
+            "-        require(balances[msg.sender] >= amount);",
+            "         balances[msg.sender] -= amount;",
+        ].join("\n");
+        assert_eq!(ChangedLines::parse(&diff).ranges("Vault.sol"), Some(&[(5, 5)][..]));
+    }
+
+    // a deletion at the top of a file marks its first line
+    #[test]
+    fn deleted_first_line() {
+        let diff = ["+++ b/Vault.sol", "@@ -1 +0,0 @@", "-// SPDX-License-Identifier: MIT"].join("\n");
+        assert_eq!(ChangedLines::parse(&diff).ranges("Vault.sol"), Some(&[(1, 1)][..]));
+    }
+
+    // with two changed files ending the same way, the exact path wins, then the longest common suffix
+    #[test]
+    fn colliding_paths() {
+        let diff = [
+            "diff --git a/Token.sol b/Token.sol",
+            "--- a/Token.sol",
+            "+++ b/Token.sol",
+            "@@ -1 +1 @@",
+            "-// SPDX-License-Identifier: UNLICENSED",
+            "+// SPDX-License-Identifier: MIT",
+            "diff --git a/src/Token.sol b/src/Token.sol",
+            "--- a/src/Token.sol",
+            "+++ b/src/Token.sol",
+            "@@ -7 +7 @@",
+            "-    uint256 totalSupply;",
+            "+    uint256 public totalSupply;",
+        ].join("\n");
+        // the order of the files changes from one map to the other
+        for _ in 0..10 {
+            let changes = ChangedLines::parse(&diff);
+            assert_eq!(changes.ranges("Token.sol"), Some(&[(1, 1)][..]));
+            assert_eq!(changes.ranges("./src/Token.sol"), Some(&[(7, 7)][..]));
+            assert_eq!(changes.ranges("contracts/src/Token.sol"), Some(&[(7, 7)][..]));
+            assert_eq!(changes.ranges("lib/Token.sol"), Some(&[(1, 1)][..]));
+        }
+    }
+}
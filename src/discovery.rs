@@ -0,0 +1,160 @@
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use regex::Regex;
+use walkdir::{DirEntry, WalkDir};
+
+// Directories holding dependencies, skipped unless --include-deps
+static DEPENDENCY_DIRS: [&str; 3] = ["lib", "node_modules", "dependencies"];
+// Directories holding build output, always skipped
+static BUILD_DIRS: [&str; 6] = ["out", "cache", "artifacts", "build", "cache_forge", "typechain-types"];
+// Directories holding tests, skipped unless --include-tests
+static TEST_DIRS: [&str; 2] = ["test", "tests"];
+
+// Which parts of a project are scanned
+#[derive(Clone, Debug, Default)]
+pub struct DiscoveryOptions {
+    pub include_tests: bool,
+    pub include_deps: bool,
+}
+
+// Kind of project a target directory is the root of
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ProjectKind {
+    Foundry,
+    Hardhat,
+    Truffle,
+}
+
+impl ProjectKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ProjectKind::Foundry => "Foundry",
+            ProjectKind::Hardhat => "Hardhat",
+            ProjectKind::Truffle => "Truffle",
+        }
+    }
+}
+
+// A Foundry/Hardhat/Truffle project found at the root of a target
+#[derive(Clone, Debug)]
+pub struct Project {
+    pub kind: ProjectKind,
+    // directory with the contracts, relative to the project root
+    pub sources: PathBuf,
+    // extra dependency directories from the project configuration
+    pub libs: Vec<String>,
+}
+
+// looks for a project configuration file in `root`
+// returns the project and its configured source directory
+pub fn detect_project(root: &Path) -> Option<Project> {
+    if let Ok(config) = fs::read_to_string(root.join("foundry.toml")) {
+        let profile = toml::from_str::<toml::Table>(&config).ok()
+            .and_then(|table| table.get("profile")?.get("default")?.as_table().cloned())
+            .unwrap_or_default();
+        let sources = profile.get("src").and_then(|src| src.as_str()).unwrap_or("src");
+        let libs = profile.get("libs").and_then(|libs| libs.as_array())
+            .map(|libs| libs.iter().filter_map(|lib| lib.as_str()).map(|lib| lib.to_string()).collect())
+            .unwrap_or_default();
+        return Some(Project { kind: ProjectKind::Foundry, sources: PathBuf::from(sources), libs });
+    }
+    for config_name in ["hardhat.config.ts", "hardhat.config.js", "hardhat.config.cjs", "hardhat.config.mjs"] {
+        if let Ok(config) = fs::read_to_string(root.join(config_name)) {
+            let sources = config_value(&config, "sources").unwrap_or_else(|| "contracts".to_string());
+            return Some(Project { kind: ProjectKind::Hardhat, sources: PathBuf::from(sources), libs: vec![] });
+        }
+    }
+    for config_name in ["truffle-config.js", "truffle.js"] {
+        if let Ok(config) = fs::read_to_string(root.join(config_name)) {
+            let sources = config_value(&config, "contracts_directory").unwrap_or_else(|| "contracts".to_string());
+            return Some(Project { kind: ProjectKind::Truffle, sources: PathBuf::from(sources), libs: vec![] });
+        }
+    }
+    None
+}
+
+// reads a `key: "value"` string entry of a JavaScript/TypeScript config file
+fn config_value(config: &str, key: &str) -> Option<String> {
+    let re = Regex::new(&format!(r#"\b{}\s*:\s*["'`]([^"'`]+)["'`]"#, key)).expect("(config_value) - invalid regex");
+    let value = re.captures(config)?.get(1)?.as_str();
+    Some(value.trim_start_matches("./").to_string())
+}
+
+// A solidity file to analyze
+// `path` is used to open the file, `relative` is the path shown in reports
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct SourceFile {
+    pub path: PathBuf,
+    pub relative: String,
+}
+
+impl SourceFile {
+    pub fn new(path: PathBuf) -> SourceFile {
+        let relative = display_path(&path);
+        SourceFile { path, relative }
+    }
+}
+
+// formats a path for reports: `/` separators and no leading `./`
+pub fn display_path(path: &Path) -> String {
+    let parts: Vec<String> = path.components()
+        .filter(|component| !matches!(component, Component::CurDir))
+        .map(|component| match component {
+            Component::RootDir => String::new(),
+            other => other.as_os_str().to_string_lossy().to_string(),
+        })
+        .collect();
+    if parts.is_empty() { ".".to_string() } else { parts.join("/") }
+}
+
+// find the solidity files of a target given on the command line
+// a project root is scanned from its configured source directory, unless
+// tests or dependencies were asked for, which live outside of it
+// returns the files found, or an error if the target does not exist
+pub fn solidity_files_in_target(target: &Path, options: &DiscoveryOptions) -> Result<Vec<SourceFile>, String> {
+    if target.is_dir() {
+        let mut extra_excludes = vec![];
+        let mut root = target.to_path_buf();
+        if let Some(project) = detect_project(target) {
+            extra_excludes = project.libs;
+            let only_sources = !options.include_tests && !options.include_deps;
+            if only_sources && target.join(&project.sources).is_dir() {
+                root = target.join(&project.sources);
+            }
+        }
+        return Ok(is_solidity_file(&root, options, &extra_excludes).into_iter().map(|relative| SourceFile::new(root.join(relative))).collect());
+    }
+    if target.is_file() {
+        return Ok(vec![SourceFile::new(target.to_path_buf())]);
+    }
+    Err(format!("(main) - Target not found: {}", target.display()))
+}
+
+// returns true if a directory met while walking a target should not be scanned
+fn is_excluded_dir(entry: &DirEntry, options: &DiscoveryOptions, extra_excludes: &[String]) -> bool {
+    if entry.depth() == 0 || !entry.file_type().is_dir() {
+        return false;
+    }
+    let name = entry.file_name().to_string_lossy();
+    BUILD_DIRS.contains(&name.as_ref())
+        || (!options.include_deps && (DEPENDENCY_DIRS.contains(&name.as_ref()) || extra_excludes.iter().any(|lib| lib.trim_end_matches('/') == name)))
+        || (!options.include_tests && TEST_DIRS.contains(&name.as_ref()))
+}
+
+// find solidity files, skipping dependency, build and test directories
+// returns the paths of all the solidity files found, relative to `path_name`
+pub fn is_solidity_file(path_name: &Path, options: &DiscoveryOptions, extra_excludes: &[String]) -> Vec<PathBuf>{
+    let mut files_names: Vec<PathBuf> = vec![];
+    let walker = WalkDir::new(path_name).sort_by_file_name().into_iter()
+        .filter_entry(|entry| !is_excluded_dir(entry, options, extra_excludes));
+    for entry in walker.filter_map(|e| e.ok()){
+        let file_name = entry.file_name().to_string_lossy();
+        let is_test = file_name.ends_with(".t.sol");
+        if entry.file_type().is_file() && file_name.ends_with(".sol") && (options.include_tests || !is_test){
+            if let Ok(relative) = entry.path().strip_prefix(path_name) {
+                files_names.push(relative.to_path_buf())
+            }
+        }
+    }
+    files_names
+}
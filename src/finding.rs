@@ -0,0 +1,112 @@
+use crate::regexrules::Severity;
+
+// A match of a rule in a scanned file
+// lines and columns are 1-based, columns count characters,
+// `start`/`end` are the byte span of the match in the file,
+// `contract`/`function` the declarations enclosing it, when there are any,
+// `related` other places of the file that explain it
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Finding {
+    pub rule_id: String,
+    pub severity: Severity,
+    pub file: String,
+    pub start: usize,
+    pub end: usize,
+    pub start_line: usize,
+    pub start_column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
+    pub snippet: String,
+    pub contract: Option<String>,
+    pub function: Option<String>,
+    pub related: Vec<Related>,
+}
+
+// Another place of the file a finding points to, e.g. the state write following a call
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Related {
+    pub message: String,
+    pub start: usize,
+    pub end: usize,
+    pub start_line: usize,
+    pub start_column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
+    pub snippet: String,
+}
+
+impl Related {
+    // builds a related location from its byte span in `source`
+    pub fn new(message: &str, source: &str, start: usize, end: usize) -> Related {
+        let (start_line, start_column) = line_and_column(source, start);
+        let (end_line, end_column) = line_and_column(source, end);
+        Related {
+            message: message.to_string(),
+            start,
+            end,
+            start_line,
+            start_column,
+            end_line,
+            end_column,
+            snippet: source[start..end].to_string(),
+        }
+    }
+}
+
+impl Finding {
+    // builds a finding from the byte span of a match in `source`
+    pub fn new(rule_id: &str, severity: Severity, file: &str, source: &str, start: usize, end: usize) -> Finding {
+        let (start_line, start_column) = line_and_column(source, start);
+        let (end_line, end_column) = line_and_column(source, end);
+        Finding {
+            rule_id: rule_id.to_string(),
+            severity,
+            file: file.to_string(),
+            start,
+            end,
+            start_line,
+            start_column,
+            end_line,
+            end_column,
+            snippet: source[start..end].to_string(),
+            contract: None,
+            function: None,
+            related: vec![],
+        }
+    }
+
+    // where the finding is in the code, e.g. `Vault.withdraw()`, `Vault` or `withdraw()`
+    pub fn scope(&self) -> Option<String> {
+        match (&self.contract, &self.function) {
+            (Some(contract), Some(function)) => Some(format!("{}.{}", contract, function)),
+            (Some(name), None) | (None, Some(name)) => Some(name.clone()),
+            (None, None) => None,
+        }
+    }
+
+    // byte offset of the first non-blank character of the match
+    pub fn anchor(&self) -> usize {
+        self.start + self.snippet.len() - self.snippet.trim_start().len()
+    }
+
+    // human readable location, e.g. `(Token.sol Line-12 Token.transfer()) -> .transferFrom(`,
+    // followed by one `    message: (Token.sol Line-14) -> ...` line per related location
+    pub fn location(&self) -> String {
+        let mut location = match self.scope() {
+            Some(scope) => format!("({} Line-{} {}) -> {}", self.file, self.start_line, scope, self.snippet),
+            None => format!("({} Line-{}) -> {}", self.file, self.start_line, self.snippet),
+        };
+        for related in &self.related {
+            location.push_str(&format!("\n    {}: ({} Line-{}) -> {}", related.message, self.file, related.start_line, related.snippet));
+        }
+        location
+    }
+}
+
+// returns the 1-based line and column (in characters) of a byte offset
+pub fn line_and_column(text: &str, offset: usize) -> (usize, usize) {
+    let before = &text[..offset];
+    let line = before.matches('\n').count() + 1;
+    let line_start = before.rfind('\n').map(|i| i + 1).unwrap_or(0);
+    (line, before[line_start..].chars().count() + 1)
+}
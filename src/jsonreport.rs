@@ -0,0 +1,122 @@
+use std::fs::File;
+use std::path::Path;
+use serde::Serialize;
+use crate::finding::Finding;
+use crate::regexrules::Rule;
+use crate::scope::ScopeSummary;
+
+// Version of the JSON output: the major part is bumped whenever a field is
+// renamed or removed, the minor part whenever a field is added
+pub static SCHEMA_VERSION: &str = "1.6.0";
+
+// Name of the JSON report written into the output directory
+pub static REPORT_NAME: &str = "auditooor-results.json";
+
+#[derive(Serialize)]
+struct Report<'a> {
+    schema_version: &'static str,
+    tool: Tool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    scope: Option<&'a ScopeSummary>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    baseline: Option<&'a BaselineSummary>,
+    findings: Vec<JsonFinding<'a>>,
+    suppressed: Vec<Suppressed<'a>>,
+}
+
+// Outcome of comparing a run with a baseline: the reported findings are the new ones
+#[derive(Serialize)]
+pub struct BaselineSummary {
+    pub new: usize,
+    pub fixed: usize,
+}
+
+// How many findings of a rule were silenced by suppression comments
+#[derive(Serialize)]
+struct Suppressed<'a> {
+    rule_id: &'a str,
+    count: usize,
+}
+
+#[derive(Serialize)]
+struct Tool {
+    name: &'static str,
+    version: &'static str,
+}
+
+// A finding as written to the JSON report
+#[derive(Serialize)]
+struct JsonFinding<'a> {
+    rule_id: &'a str,
+    severity: &'static str,
+    title: &'a str,
+    file: &'a str,
+    contract: Option<&'a str>,
+    function: Option<&'a str>,
+    line: usize,
+    column: usize,
+    end_line: usize,
+    end_column: usize,
+    start_byte: usize,
+    end_byte: usize,
+    matched: &'a str,
+    recommendation: &'a str,
+    related: Vec<JsonRelated<'a>>,
+}
+
+// Another place of the file a finding points to
+#[derive(Serialize)]
+struct JsonRelated<'a> {
+    message: &'a str,
+    line: usize,
+    column: usize,
+    end_line: usize,
+    end_column: usize,
+    start_byte: usize,
+    end_byte: usize,
+    matched: &'a str,
+}
+
+// writes the findings of a scan as a JSON document
+// the in-scope files are listed when a scope file was given, the baseline outcome when a baseline was
+pub fn write_json(path: &Path, rules: &[&Rule], findings: &[Finding], suppressed: &[(&Rule, usize)], baseline: Option<&BaselineSummary>, scope: Option<&ScopeSummary>) -> Result<(), String> {
+    let findings = findings.iter().map(|finding| {
+        let rule = rules.iter().find(|rule| rule.id == finding.rule_id);
+        JsonFinding {
+            rule_id: &finding.rule_id,
+            severity: finding.severity.name(),
+            title: rule.map(|rule| rule.title.as_str()).unwrap_or_default(),
+            file: &finding.file,
+            contract: finding.contract.as_deref(),
+            function: finding.function.as_deref(),
+            line: finding.start_line,
+            column: finding.start_column,
+            end_line: finding.end_line,
+            end_column: finding.end_column,
+            start_byte: finding.start,
+            end_byte: finding.end,
+            matched: &finding.snippet,
+            recommendation: rule.map(|rule| rule.recommendation.as_str()).unwrap_or_default(),
+            related: finding.related.iter().map(|related| JsonRelated {
+                message: &related.message,
+                line: related.start_line,
+                column: related.start_column,
+                end_line: related.end_line,
+                end_column: related.end_column,
+                start_byte: related.start,
+                end_byte: related.end,
+                matched: &related.snippet,
+            }).collect(),
+        }
+    }).collect();
+    let report = Report {
+        schema_version: SCHEMA_VERSION,
+        tool: Tool { name: env!("CARGO_PKG_NAME"), version: env!("CARGO_PKG_VERSION") },
+        scope,
+        baseline,
+        findings,
+        suppressed: suppressed.iter().map(|(rule, count)| Suppressed { rule_id: &rule.id, count: *count }).collect(),
+    };
+    let file = File::create(path).map_err(|err| format!("(write_json) - It was not possible to create {}: {}", path.display(), err))?;
+    serde_json::to_writer_pretty(file, &report).map_err(|err| format!("(write_json) - Write failed: {}", err))
+}
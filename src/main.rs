@@ -1,190 +1,536 @@
 extern crate colored;
 use std::io::Write;
-use fancy_regex::Regex;
-use walkdir::{WalkDir};
+use std::process::ExitCode;
 use colored::*;
+use clap::Parser;
 use std::fs::File;
 use std::fs;
+mod baseline;
+mod cli;
+mod config;
+mod contest;
+mod detectors;
+mod diff;
+mod discovery;
+mod finding;
+mod jsonreport;
+mod prefilter;
 mod regexrules;
-use regexrules::RulesDataBase;
-use std::time::Instant;
+mod rulepack;
+mod ruleset;
+mod scanner;
+mod scope;
+mod sarif;
+mod selftest;
+mod solidity;
+mod suppression;
+mod tokenizer;
+use cli::OutputFormat;
+use discovery::SourceFile;
+use finding::Finding;
+use regexrules::{Rule, Severity};
+use ruleset::RuleSet;
+use scanner::FileFindings;
+use std::collections::HashMap;
+use std::path::Path;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
 
 
 
-// Struct to store all the vulns/gas optimization and where they occur in the target
+// How often --watch checks the targets for changes
+static WATCH_INTERVAL: Duration = Duration::from_millis(500);
 
-// Initialize the struct
-impl Default for RulesDataBase {
-    fn default () -> RulesDataBase {
-        RulesDataBase {id: String::new(), title: String::new(), description: String::new(), location: Vec::new(), rule: String::new(), recommendation: String::new()}
-    }
-}
+// Struct to store all the vulns/gas optimization and where they occur in the target
 
-static BANNER: &'static str = "
-_______  __   __  ______   ___   _______  _______  _______  _______  ______   
-|   _   ||  | |  ||      | |   | |       ||       ||       ||       ||    _ |  
-|  |_|  ||  | |  ||  _    ||   | |_     _||   _   ||   _   ||   _   ||   | ||  
-|       ||  |_|  || | |   ||   |   |   |  |  | |  ||  | |  ||  | |  ||   |_||_ 
+static BANNER: &str = "
+_______  __   __  ______   ___   _______  _______  _______  _______  ______
+|   _   ||  | |  ||      | |   | |       ||       ||       ||       ||    _ |
+|  |_|  ||  | |  ||  _    ||   | |_     _||   _   ||   _   ||   _   ||   | ||
+|       ||  |_|  || | |   ||   |   |   |  |  | |  ||  | |  ||  | |  ||   |_||_
 |       ||       || |_|   ||   |   |   |  |  |_|  ||  |_|  ||  |_|  ||    __  |
 |   _   ||       ||       ||   |   |   |  |       ||       ||       ||   |  | |
 |__| |__||_______||______| |___|   |___|  |_______||_______||_______||___|  |_|";
 
-fn main() {
-    banner();
-    let path_name: String<> = String::from("contracts"); // The directory where the solidity files are located
-    let mut gasopresults: Vec<RulesDataBase>;
-    let mut lowresults: Vec<RulesDataBase>;
-    let mut ncresults: Vec<RulesDataBase>;
-    let files_names: Vec<String> = is_solidity_file(&path_name); // Vector with solidity files to analyze
+fn main() -> ExitCode {
+    let args = cli::Args::parse();
+    if let Some(cli::Command::TestRules { packs }) = &args.command {
+        return test_rules(packs);
+    }
+    if !args.quiet && !args.no_banner {
+        banner();
+    }
+
+    // Only keep the in-scope files when a scope file was given
+    let scope = match args.scope.as_deref().map(scope::load_scope).transpose() {
+        Ok(scope) => scope,
+        Err(err) => {
+            eprintln!("{}", err.red());
+            return ExitCode::from(2);
+        }
+    };
+    // The solidity files to analyze, found in the targets
+    let files_names = match find_files(&args, scope.as_ref(), true) {
+        Ok(files_names) => files_names,
+        Err(err) => {
+            eprintln!("{}", err.red());
+            return ExitCode::from(2);
+        }
+    };
+    let scope_summary = scope.as_ref().map(|scope| scope::ScopeSummary::new(scope, &files_names));
 
-    let gasoprules = regexrules::gas_op_issues();
-    let lowrules = regexrules::low_issues();
-    let ncrules = regexrules::non_critical_issues();
+    // Severities, disabled rules and access control modifiers of the project
+    let config = match config::Config::load(args.config.as_deref()) {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("{}", err.red());
+            return ExitCode::from(2);
+        }
+    };
 
-    println!("\n{}","Solidity files found:");
-    for file in &files_names{
-        println!("{}", file.on_white().black())
+    let (mut all_rules, mut all_detectors) = match load_rules(&args.packs, &config) {
+        Ok(loaded) => loaded,
+        Err(errors) => {
+            for err in errors {
+                eprintln!("{}", err.red());
+            }
+            return ExitCode::from(2);
+        }
+    };
+    let known_rules: Vec<&Rule> = all_rules.iter().chain(all_detectors.iter().map(|(rule, _)| rule)).collect();
+    for id in config.unknown_ids(&known_rules) {
+        eprintln!("{}", format!("(main) - Configured rule {} does not exist", id).yellow());
     }
-    print!("\n{}","---------Analyzing---------\n\n");
+    all_rules.iter_mut().chain(all_detectors.iter_mut().map(|(rule, _)| rule)).for_each(|rule| config.apply(rule));
 
-    let gasop_file = std::fs::File::create("Gas-Optimization Results.md").expect("(Writing_to_a_file error) -> It was not possible to create the output");
-    let low_file = std::fs::File::create("Low Severity Results.md").expect("(Writing_to_a_file error) -> It was not possible to create the output");
-    let nc_file = std::fs::File::create("Non Critical Results.md").expect("(Writing_to_a_file error) -> It was not possible to create the output");
-    let files_list = [gasop_file,low_file,nc_file];
+    // Rules of every selected severity, minus the excluded and disabled ones, compiled once for the whole scan
+    let mut severities: Vec<Severity> = Severity::ALL.into_iter().filter(|severity| args.wants(*severity)).collect();
+    let selected = |rule: &Rule| args.wants(rule.severity) && !args.excludes(&rule.id) && !config.disables(&rule.id);
+    let selected_rules: Vec<Rule> = severities.iter()
+        .flat_map(|severity| all_rules.iter().filter(move |rule| rule.severity == *severity))
+        .filter(|rule| selected(rule))
+        .cloned()
+        .collect();
+    let selected_detectors: Vec<detectors::ConfiguredDetector> = all_detectors.into_iter()
+        .filter(|(rule, _)| selected(rule))
+        .collect();
+    let rule_set = match RuleSet::compile(selected_rules, selected_detectors) {
+        Ok(rule_set) => rule_set,
+        Err(errors) => {
+            for err in errors {
+                eprintln!("{}", err.red());
+            }
+            return ExitCode::from(2);
+        }
+    };
+    // Only the severities with rules get a report
+    severities.retain(|severity| rule_set.rules().any(|rule| rule.severity == *severity));
+    let mut rules_run: Vec<&Rule> = rule_set.rules().collect();
+    rules_run.sort_by_key(|rule| severities.iter().position(|severity| *severity == rule.severity));
+
+    if !args.quiet {
+        println!("\nSolidity files found:");
+        for file in &files_names{
+            println!("{}", file.relative.on_white().black())
+        }
+        if let Some(summary) = &scope_summary {
+            println!("\nIn scope: {} files, {} nSLOC", summary.file_count, summary.total_nsloc);
+        }
+        print!("\n---------Analyzing---------\n\n");
+    }
+
+    if let Err(err) = fs::create_dir_all(&args.out_dir) {
+        eprintln!("{}", format!("(main) - Failed to create the output directory {}: {}", args.out_dir.display(), err).red());
+        return ExitCode::from(2);
+    }
+
+    // Findings of a previous run that are not reported again
+    let known_findings = match args.baseline.as_deref().map(baseline::Baseline::load).transpose() {
+        Ok(known_findings) => known_findings,
+        Err(err) => {
+            eprintln!("{}", err.red());
+            return ExitCode::from(2);
+        }
+    };
+
+    // Lines changed since the base ref, when only those are reviewed
+    let changes = match args.diff.as_deref().map(|base_ref| diff::ChangedLines::load(base_ref, args.diff_whole_function)).transpose() {
+        Ok(changes) => changes,
+        Err(err) => {
+            eprintln!("{}", err.red());
+            return ExitCode::from(2);
+        }
+    };
+
+    // Where the contest report instances link to
+    let repository = match args.repo_url.as_deref().zip(args.commit.as_deref()).map(|(url, commit)| contest::Repository::new(url, commit, &args.targets[0])).transpose() {
+        Ok(repository) => repository,
+        Err(err) => {
+            eprintln!("{}", err.red());
+            return ExitCode::from(2);
+        }
+    };
 
     let now = Instant::now();
-    for file in &files_names{
-        gasopresults = analyzing(file.to_string(), &path_name.to_string(), &gasoprules); // Analyzing every single solidity file
-        lowresults = analyzing(file.to_string(), &path_name.to_string(), &lowrules);
-        ncresults = analyzing(file.to_string(), &path_name.to_string(), &ncrules);
-        let results_list = [gasopresults,lowresults,ncresults];
-
-        for (index, results) in results_list.into_iter().enumerate() {
-            
-            if writing_to_a_file(&results, &files_list[index], file.to_string()){ // writing the results into a file
-                print!("{}", "Output file created Successfully".green())
+    let mut results: Vec<FileFindings> = vec![];
+    for result in scanner::scan_files(&files_names, &rule_set, changes.as_ref(), jobs(&args)) { // Analyzing every single solidity file
+        match result {
+            Ok(file_findings) => {
+                print_warnings(&file_findings);
+                results.push(file_findings);
             }
-            print!("\n{}{}\n","---------Results: ".green(),file.green());
-
-            // This loop will print all the vulns/gas found by the analyzing function
-            for result in results{
-                if result.location.len() > 0{
-                    print!("---->{}\n",result.title.bright_yellow().underline());
-                    print!("     {}\n",result.description.bright_yellow());
-                    for (index, bug )in result.location.into_iter().enumerate(){
-                        print!("{}{}#:\n","Case ".blue(), index);
-                        print!("{}\n\n", bug.bright_red().bold());
-                    }
-                }
+            Err(err) => {
+                eprintln!("{}", err.red());
+                return ExitCode::from(2);
             }
         }
     }
 
-    let new_now = Instant::now();
-    println!("{}{:?}","Time to complete scan: ", new_now.duration_since(now));
+    let reporting = Reporting { args: &args, severities, rules_run, scope_summary, known_findings, repository };
+    let all_findings = match write_reports(&reporting, &files_names, &results, &vec![!args.quiet; files_names.len()]) {
+        Ok(all_findings) => all_findings,
+        Err(err) => {
+            eprintln!("{}", err.red());
+            return ExitCode::from(2);
+        }
+    };
 
+    if !args.quiet {
+        let new_now = Instant::now();
+        println!("Time to complete scan: {:?}", new_now.duration_since(now));
+    }
 
+    if args.watch {
+        watch(reporting, &rule_set, scope.as_ref(), changes, files_names, results);
+    }
+
+    let worst_severity = all_findings.iter().map(|finding| finding.severity).max();
+    match (args.fail_on, worst_severity) {
+        (Some(threshold), Some(worst)) if worst >= threshold => ExitCode::from(1),
+        _ => ExitCode::SUCCESS,
+    }
 }
 
+// loads the rule packs, and the built-in detectors which come with the default rules
+// every detector is paired with a copy of its rule that the project configuration can change
+fn load_rules(packs: &cli::RulePacks, config: &config::Config) -> Result<(Vec<Rule>, Vec<detectors::ConfiguredDetector>), Vec<String>> {
+    let rules = rulepack::load_rules(!packs.no_default_rules, &packs.rules_dir, &packs.rule_files)?;
+    let detectors = detectors::all(config).into_iter()
+        .filter(|_| !packs.no_default_rules)
+        .map(|detector| (detector.rule().clone(), detector))
+        .collect();
+    Ok((rules, detectors))
+}
 
-fn line_from_bytes(text: &str, start: usize, _end: usize) -> i128{
-    let bytes = text.as_bytes();
-    let mut count = 1;
-    
-    for (i, &item) in bytes.iter().enumerate() {
-        // print!("{}",i);
-        if i == start {
-            return count;
+// checks the rules against their snippets and that their ids are unique
+// exits with 1 when a check fails, 2 when the rules can not be loaded
+fn test_rules(packs: &cli::RulePacks) -> ExitCode {
+    let (rules, detectors) = match load_rules(packs, &config::Config::default()) {
+        Ok(loaded) => loaded,
+        Err(errors) => {
+            for err in errors {
+                eprintln!("{}", err.red());
+            }
+            return ExitCode::from(2);
+        }
+    };
+    let duplicates: Vec<String> = selftest::duplicate_ids(rules.iter().chain(detectors.iter().map(|(rule, _)| rule)))
+        .into_iter().map(|id| id.to_string()).collect();
+    let tests = match selftest::test_rules(rules, detectors) {
+        Ok(tests) => tests,
+        Err(errors) => {
+            for err in errors {
+                eprintln!("{}", err.red());
+            }
+            return ExitCode::from(2);
         }
-        if item == b'\n'{ 
-            count = count + 1;
+    };
+
+    for test in &tests {
+        let outcome = match (test.failures.is_empty(), test.snippets()) {
+            (false, _) => "FAILED".red(),
+            (true, 0) => "no snippets".yellow(),
+            (true, _) => "ok".green(),
+        };
+        println!("{} {} ... {}", test.rule.id, test.rule.title, outcome);
+        for failure in &test.failures {
+            println!("{}", failure);
         }
     }
-    count
+    for id in &duplicates {
+        println!("{}", format!("Rule id {} is used by more than one rule", id).red());
+    }
+    let failed = tests.iter().filter(|test| !test.failures.is_empty()).count();
+    let untested = tests.iter().filter(|test| test.snippets() == 0).count();
+    println!("\n{} rules tested: {} passed, {} failed, {} without snippets, {} duplicate ids", tests.len(), tests.len() - failed - untested, failed, untested, duplicates.len());
+    if failed > 0 || !duplicates.is_empty() { ExitCode::from(1) } else { ExitCode::SUCCESS }
+}
+
+// What the reports of a scan are built from, besides the files and their findings
+struct Reporting<'a> {
+    args: &'a cli::Args,
+    severities: Vec<Severity>,
+    rules_run: Vec<&'a Rule>,
+    scope_summary: Option<scope::ScopeSummary>,
+    known_findings: Option<baseline::Baseline>,
+    repository: Option<contest::Repository<'a>>,
 }
 
-// analyze the code using rules
-// returns a struct with the results
-fn analyzing(file_name: String, path_name: &String, rules: &Vec<RulesDataBase>) -> Vec<RulesDataBase>{
-   
-    let mut analyzed_block: Vec<RulesDataBase> = rules.to_vec();
-    let foo = fs::read_to_string(format!("{}/{}", path_name, file_name)).expect("(analyzing) - Failed to open the file");
-
-    for (indexy, rule )in rules.into_iter().enumerate(){
-        let re = Regex::new(rule.rule.as_str()).unwrap();
-        let mut matches = re.find_iter(&foo);
-        while let Some(value) = matches.next() {
-            let m = value.unwrap();
-            let linenumber = line_from_bytes(&foo, m.start(), m.end());
-
-            //Removing comment matches
-            let comment_check = m.as_str().trim();
-            if !comment_check.starts_with("/"){
-                analyzed_block[indexy].location.push(format!("{}{}{}{}{} -> {}","(",file_name," Line-",linenumber,")", m.as_str() ));
+// number of files analyzed in parallel
+fn jobs(args: &cli::Args) -> usize {
+    args.jobs.unwrap_or_else(|| thread::available_parallelism().map(|n| n.get()).unwrap_or(1))
+}
+
+// finds the solidity files of the targets, in path order, and keeps the in-scope ones
+// detected projects and unmatched scope entries are only reported on the first run
+// returns the files, or why a target could not be read
+fn find_files(args: &cli::Args, scope: Option<&scope::Scope>, first_run: bool) -> Result<Vec<SourceFile>, String> {
+    let mut files_names: Vec<SourceFile> = vec![];
+    let discovery_options = args.discovery_options();
+    for target in &args.targets {
+        if let Some(project) = discovery::detect_project(target).filter(|_| first_run && !args.quiet) {
+            println!("{} project detected in {}, sources in {}", project.kind.name(), target.display(), project.sources.display());
+        }
+        files_names.append(&mut discovery::solidity_files_in_target(target, &discovery_options)?);
+    }
+    // The same file can be reached from overlapping targets
+    files_names.sort_by(|a, b| a.relative.cmp(&b.relative));
+    files_names.dedup_by(|a, b| a.relative == b.relative);
+
+    if let Some(scope) = scope {
+        for entry in scope.unmatched(&files_names).into_iter().filter(|_| first_run) {
+            eprintln!("{}", format!("(main) - Scope entry {} does not match any file", entry).yellow());
+        }
+        files_names.retain(|file| scope.contains(&file.relative));
+    }
+    Ok(files_names)
+}
+
+// writes every requested report from the findings of the scanned files,
+// and prints the findings of the files flagged in `printed`
+// returns the reported findings, or why a report could not be written
+fn write_reports(reporting: &Reporting, files_names: &[SourceFile], results: &[FileFindings], printed: &[bool]) -> Result<Vec<Finding>, String> {
+    let args = reporting.args;
+    let rules_run = &reporting.rules_run;
+    let scope_summary = reporting.scope_summary.as_ref();
+
+    let mut files_list: Vec<(Severity, File)> = vec![];
+    for severity in reporting.severities.iter().filter(|_| args.writes(OutputFormat::Markdown)) {
+        let report_path = args.out_dir.join(severity.report_name());
+        let mut file = File::create(&report_path).map_err(|err| format!("(Writing_to_a_file error) -> It was not possible to create {}: {}", report_path.display(), err))?;
+        if let Some(summary) = scope_summary {
+            file.write_all(summary.markdown_header().as_bytes()).expect("(Writing_to_a_file error) -> Write Failed");
+        }
+        files_list.push((*severity, file))
+    }
+
+    // Every run starts from the whole baseline
+    let mut known_findings = reporting.known_findings.clone();
+    let mut all_findings: Vec<Finding> = vec![];
+    let mut suppressed: Vec<Finding> = vec![];
+    let mut current_findings: Vec<Finding> = vec![];
+    for ((file, file_findings), print) in files_names.iter().zip(results).zip(printed) {
+        let mut findings = file_findings.findings.clone();
+        suppressed.extend(file_findings.suppressed.iter().cloned());
+        if args.write_baseline.is_some() {
+            current_findings.extend(findings.iter().cloned());
+        }
+        if let Some(known_findings) = &mut known_findings {
+            findings.retain(|finding| !known_findings.take(finding));
+        }
+
+        for (severity, report) in &files_list {
+            let rules: Vec<&Rule> = rules_run.iter().copied().filter(|rule| rule.severity == *severity).collect();
+            let severity_findings: Vec<&Finding> = findings.iter().filter(|finding| finding.severity == *severity).collect();
+            if writing_to_a_file(&rules, &severity_findings, report, &file.relative) && *print { // writing the results into a file
+                print!("{}", "Output file created Successfully".green())
             }
         }
-        
-    } 
-    analyzed_block
+        if *print {
+            println!("\n{}{}","---------Results: ".green(),file.relative.green());
+            print_findings(rules_run, &findings);
+        }
+        all_findings.extend(findings);
+    }
+
+    // findings of files or rules left out of this run (--diff, --scope, --only, --exclude-rule) are not fixed,
+    // nor are those still found on the lines --diff left out
+    if let Some(known_findings) = &mut known_findings {
+        for finding in results.iter().flat_map(|file_findings| &file_findings.unchanged) {
+            known_findings.take(finding);
+        }
+    }
+    let analyzed: Vec<&str> = files_names.iter().zip(results).filter(|(_, file_findings)| file_findings.analyzed).map(|(file, _)| file.relative.as_str()).collect();
+    let scanned = |rule_id: &str, file: &str| rules_run.iter().any(|rule| rule.id == rule_id) && analyzed.contains(&file);
+    let baseline_summary = known_findings.as_ref().map(|known_findings| jsonreport::BaselineSummary { new: all_findings.len(), fixed: known_findings.fixed(scanned) });
+    if let (Some(summary), false) = (&baseline_summary, args.quiet) {
+        println!("\n{}", format!("Baseline: {} new findings, {} fixed", summary.new, summary.fixed).green());
+    }
+    if let Some(path) = &args.write_baseline {
+        baseline::write_baseline(path, &current_findings)?;
+    }
+
+    let suppressed_counts = suppression::counts(rules_run, &suppressed);
+    for (severity, report) in &files_list {
+        let counts: Vec<(&Rule, usize)> = suppressed_counts.iter().copied().filter(|(rule, _)| rule.severity == *severity).collect();
+        writing_suppressed(&counts, report);
+    }
+    if !args.quiet && !suppressed_counts.is_empty() {
+        println!("\n{}", "---------Suppressed findings---------".green());
+        for (rule, count) in &suppressed_counts {
+            println!("{} {}: {}", rule.id, rule.title.bright_yellow(), count);
+        }
+        println!();
+    }
+
+    if args.writes(OutputFormat::Json) {
+        jsonreport::write_json(&args.out_dir.join(jsonreport::REPORT_NAME), rules_run, &all_findings, &suppressed_counts, baseline_summary.as_ref(), scope_summary)?;
+    }
+
+    if args.writes(OutputFormat::Contest) {
+        contest::write_contest_reports(&args.out_dir, rules_run, &all_findings, files_names, scope_summary, reporting.repository.as_ref())?;
+    }
+
+    if args.writes(OutputFormat::Sarif) {
+        sarif::write_sarif(&args.out_dir.join(sarif::REPORT_NAME), rules_run, &all_findings, &suppressed, known_findings.is_some())?;
+    }
+    Ok(all_findings)
 }
 
-// find solidity files
-// returns a vector with all the solidity files found with
-fn is_solidity_file(path_name: &String) -> Vec<String>{
-    let mut files_names: Vec<String> = vec![];
-    for entry in WalkDir::new(&path_name).into_iter().filter_map(|e| e.ok()){
-    if entry.file_name()
-                    .to_str()
-                    .map(|s| s.ends_with(".sol"))
-                    .unwrap_or(false){
-                        files_names.push(entry.file_name().to_string_lossy().to_string())
-                    }
-    
-    }
-    // println!("{}","Len:",files_names.len());
-    files_names
-                
+// modification time and size of a file, None if it can not be read
+fn stamp(path: &Path) -> Option<(SystemTime, u64)> {
+    let metadata = fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+// polls the targets for new, changed and removed solidity files, re-analyzes
+// the files that changed and rewrites the reports, until interrupted
+fn watch(mut reporting: Reporting, rule_set: &RuleSet, scope: Option<&scope::Scope>, mut changes: Option<diff::ChangedLines>, mut files_names: Vec<SourceFile>, mut results: Vec<FileFindings>) -> ! {
+    let args = reporting.args;
+    let mut stamps: Vec<Option<(SystemTime, u64)>> = files_names.iter().map(|file| stamp(&file.path)).collect();
+    if !args.quiet {
+        println!("\n{}", format!("Watching {} files for changes, press Ctrl-C to stop", files_names.len()).green());
+    }
+    loop {
+        thread::sleep(WATCH_INTERVAL);
+        let found = match find_files(args, scope, false) {
+            Ok(found) => found,
+            Err(err) => {
+                eprintln!("{}", err.red());
+                continue;
+            }
+        };
+        let found_stamps: Vec<Option<(SystemTime, u64)>> = found.iter().map(|file| stamp(&file.path)).collect();
+        let changed: Vec<bool> = found.iter().zip(&found_stamps).map(|(file, found_stamp)| {
+            match files_names.iter().position(|known| known.relative == file.relative) {
+                Some(index) => stamps[index] != *found_stamp,
+                None => true,
+            }
+        }).collect();
+        let removed = files_names.iter().any(|known| !found.iter().any(|file| file.relative == known.relative));
+        if !changed.contains(&true) && !removed {
+            continue;
+        }
+
+        let now = Instant::now();
+        // Changed lines move as files are edited
+        if let Some(base_ref) = args.diff.as_deref().filter(|base_ref| *base_ref != "-") {
+            match diff::ChangedLines::load(base_ref, args.diff_whole_function) {
+                Ok(reloaded) => changes = Some(reloaded),
+                Err(err) => eprintln!("{}", err.red()),
+            }
+        }
+        let changed_files: Vec<SourceFile> = found.iter().zip(&changed).filter(|(_, changed)| **changed).map(|(file, _)| file.clone()).collect();
+        if !args.quiet {
+            println!("\n{}", format!("---------Re-analyzing {} changed files---------", changed_files.len()).green());
+        }
+        let mut new_results = scanner::scan_files(&changed_files, rule_set, changes.as_ref(), jobs(args)).into_iter();
+        let mut previous: HashMap<String, FileFindings> = files_names.into_iter().map(|file| file.relative).zip(results).collect();
+        results = found.iter().zip(&changed).map(|(file, changed)| match changed {
+            true => new_results.next().and_then(|result| result.map_err(|err| eprintln!("{}", err.red())).ok()).inspect(print_warnings).unwrap_or_default(),
+            false => previous.remove(&file.relative).unwrap_or_default(),
+        }).collect();
+        files_names = found;
+        stamps = found_stamps;
+
+        reporting.scope_summary = scope.map(|scope| scope::ScopeSummary::new(scope, &files_names));
+        let printed: Vec<bool> = changed.iter().map(|changed| *changed && !args.quiet).collect();
+        if let Err(err) = write_reports(&reporting, &files_names, &results, &printed) {
+            eprintln!("{}", err.red());
+        }
+        if !args.quiet {
+            println!("Time to complete scan: {:?}", Instant::now().duration_since(now));
+        }
+    }
+}
+
+
+// prints the rules that failed while matching a file, whose findings may be incomplete
+fn print_warnings(file_findings: &FileFindings) {
+    for warning in &file_findings.warnings {
+        eprintln!("{}", warning.yellow());
+    }
+}
+
+// prints the findings of a file, grouped by rule
+// no return
+fn print_findings(rules: &[&Rule], findings: &[Finding]){
+    for rule in rules{
+        let rule_findings: Vec<&Finding> = findings.iter().filter(|finding| finding.rule_id == rule.id).collect();
+        if !rule_findings.is_empty(){
+            println!("---->{}",rule.title.bright_yellow().underline());
+            println!("     {}",rule.description.bright_yellow());
+            for (index, finding) in rule_findings.into_iter().enumerate(){
+                println!("{}{}#:","Case ".blue(), index);
+                print!("{}\n\n", finding.location().bright_red().bold());
+            }
+        }
+    }
 }
 
 // writes the results into a file
 // returns true if everything went well
-fn writing_to_a_file(results: &Vec<RulesDataBase>,mut file: &File, filename: String) -> bool{
+fn writing_to_a_file(rules: &[&Rule], findings: &[&Finding], mut file: &File, filename: &str) -> bool{
 
     // file.write_all(BANNER.as_bytes()).expect("(Writing_to_a_file error) -> Write Failed");
     // file.write_all(format!("{}{}{}","\n                   Hello, Welcome to Auditoor 😀","\n\n---> Made by Shaurya Veer Singh").expect("(Writing_to_a_file error) -> It was not possible to creat the output");
-    let analyzed_block: Vec<RulesDataBase> = results.to_vec();
-    let mut relevant = false;
-    for result in &analyzed_block{
-        if result.location.len() > 0{
-            relevant = true;
-        }
-    }
-    
-    if relevant {
+    if !findings.is_empty() {
         file.write_all(format!("# {}\n",filename).as_bytes()).expect("(Writing_to_a_file error) -> Write Failed");
         let mut count = 1;
-        for result in analyzed_block{
-            if result.location.len() > 0{
-                file.write_all(format!("## {}. {}\n",count,result.title).as_bytes()).expect("(Writing_to_a_file error) -> Write Failed");
-                file.write_all(format!("#### {}\n",result.description).as_bytes()).expect("(Writing_to_a_file error) -> Write Failed");
-                for (index, bug )in result.location.into_iter().enumerate(){
-                    file.write_all(format!("{}{}#:\n","Case ", index).as_bytes()).expect("(Writing_to_a_file error) -> Write Failed");
-                    file.write_all(format!("```solidity\n{}\n```\n\n", bug).as_bytes()).expect("(Writing_to_a_file error) -> Write Failed");
+        for rule in rules{
+            let rule_findings: Vec<&&Finding> = findings.iter().filter(|finding| finding.rule_id == rule.id).collect();
+            if !rule_findings.is_empty(){
+                file.write_all(format!("## {}. {}\n",count,rule.title).as_bytes()).expect("(Writing_to_a_file error) -> Write Failed");
+                file.write_all(format!("#### {}\n",rule.description).as_bytes()).expect("(Writing_to_a_file error) -> Write Failed");
+                for (index, finding) in rule_findings.into_iter().enumerate(){
+                    file.write_all(format!("Case {}#:\n", index).as_bytes()).expect("(Writing_to_a_file error) -> Write Failed");
+                    file.write_all(format!("```solidity\n{}\n```\n\n", finding.location()).as_bytes()).expect("(Writing_to_a_file error) -> Write Failed");
                 }
-                file.write_all(format!("#### {}\n",result.recommendation).as_bytes()).expect("(Writing_to_a_file error) -> Write Failed");
+                file.write_all(format!("#### {}\n",rule.recommendation).as_bytes()).expect("(Writing_to_a_file error) -> Write Failed");
                 count += 1;
             }
-            
+
         }
     }
 
     true
 }
 
+// writes how many findings of each rule were suppressed at the end of a report
+// no return
+fn writing_suppressed(counts: &[(&Rule, usize)], mut file: &File){
+    if !counts.is_empty() {
+        file.write_all(b"# Suppressed findings\n| Rule | Issue | Suppressed |\n| --- | :--- | :---: |\n").expect("(Writing_to_a_file error) -> Write Failed");
+        for (rule, count) in counts{
+            file.write_all(format!("| {} | {} | {} |\n", rule.id, rule.title, count).as_bytes()).expect("(Writing_to_a_file error) -> Write Failed");
+        }
+    }
+}
+
 // prints the banner
 // no return
 fn banner(){
     print!("{}",BANNER.bright_blue());
     println!("{}{}{}","\n                   Welcome to Auditooor 😀".blue(),"\n\n---> Made by Shaurya Veer Singh".yellow(),"\nsite: https://svskaushik.vercel.app.tech\nemail: shaurya9702@gmail.com\n\n".yellow());
-    print!("{}","---------Starting----------\n\n"); 
+    print!("---------Starting----------\n\n");
 }
@@ -0,0 +1,292 @@
+use std::iter::Peekable;
+use std::str::Chars;
+
+// Literals shorter than this select almost every file, so they are not worth prefiltering on
+const MIN_LITERAL_LEN: usize = 3;
+
+// A piece of a parsed regex, only as detailed as the literal extraction needs
+enum Node {
+    Literal(char),
+    // alternatives of a group, each a sequence of pieces
+    Group(Vec<Vec<Piece>>),
+    // anything that is not a plain character: classes, `.`, anchors, lookarounds, backreferences
+    Other,
+}
+
+struct Piece {
+    node: Node,
+    // the piece may match zero times (`?`, `*`, `{0,n}`)
+    optional: bool,
+    // the piece may match more than once (`+`, `*`, `{n,m}`)
+    repeated: bool,
+}
+
+// returns literals of which at least one appears in every match of `pattern`
+// returns None when no such literals can be extracted (case-insensitive
+// patterns, alternatives without a literal, literals that are too short),
+// in which case the rule has to run on every file
+pub fn required_literals(pattern: &str) -> Option<Vec<String>> {
+    if has_flag(pattern, 'i') || has_flag(pattern, 'x') {
+        return None;
+    }
+    let mut chars = pattern.chars().peekable();
+    let alternatives = parse_alternatives(&mut chars);
+    let literals = alternatives_literals(&alternatives)?;
+    if literals.iter().any(|literal| literal.chars().count() < MIN_LITERAL_LEN) {
+        return None;
+    }
+    Some(literals)
+}
+
+// returns true if an inline flag group, `(?flags)` or `(?flags:...)`, mentions the flag
+// e.g. `i` in `(?i)`, `(?mi)`, `(?si:...)` or `(?s-i)`
+fn has_flag(pattern: &str, flag: char) -> bool {
+    pattern.match_indices("(?").any(|(index, _)| {
+        let flags: String = pattern[index + 2..].chars().take_while(|c| c.is_ascii_alphabetic() || *c == '-').collect();
+        let closed = pattern[index + 2 + flags.len()..].starts_with([')', ':']);
+        closed && flags.contains(flag)
+    })
+}
+
+// parses `a|b|c` up to the end of the pattern or the closing `)` of the current group
+fn parse_alternatives(chars: &mut Peekable<Chars>) -> Vec<Vec<Piece>> {
+    let mut alternatives = vec![];
+    let mut sequence = vec![];
+    while let Some(c) = chars.next() {
+        let node = match c {
+            '|' => {
+                alternatives.push(std::mem::take(&mut sequence));
+                continue;
+            }
+            ')' => break,
+            '\\' => parse_escape(chars),
+            '[' => {
+                skip_class(chars);
+                Node::Other
+            }
+            '(' => parse_group(chars),
+            '.' | '^' | '$' => Node::Other,
+            c => Node::Literal(c),
+        };
+        let (optional, repeated) = parse_quantifier(chars);
+        sequence.push(Piece { node, optional, repeated });
+    }
+    alternatives.push(sequence);
+    alternatives
+}
+
+// parses what follows a `\`, the whole escape for `\x63`, `\x{63}`, `\u{..}` or `\p{L}`
+fn parse_escape(chars: &mut Peekable<Chars>) -> Node {
+    match chars.next() {
+        Some('n') => Node::Literal('\n'),
+        Some('t') => Node::Literal('\t'),
+        Some(c @ ('x' | 'u' | 'U' | 'p' | 'P')) => {
+            if chars.peek() == Some(&'{') {
+                for c in chars.by_ref() {
+                    if c == '}' {
+                        break;
+                    }
+                }
+            } else {
+                // `\x63`, `\u0063`, `\U00000063`, or the one letter class of `\pL`
+                let digits = match c {
+                    'x' => 2,
+                    'u' => 4,
+                    'U' => 8,
+                    _ => 1,
+                };
+                for _ in 0..digits {
+                    chars.next();
+                }
+            }
+            Node::Other
+        }
+        Some(c) if c.is_ascii_alphanumeric() => Node::Other, // \d \w \s \b \1 ...
+        Some(c) => Node::Literal(c),
+        None => Node::Other,
+    }
+}
+
+// skips a character class, including nested `[[:blank:]]` classes
+fn skip_class(chars: &mut Peekable<Chars>) {
+    let mut depth = 1;
+    if chars.peek() == Some(&'^') {
+        chars.next();
+    }
+    if chars.peek() == Some(&']') {
+        chars.next();
+    }
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                chars.next();
+            }
+            '[' => depth += 1,
+            ']' => {
+                depth -= 1;
+                if depth == 0 {
+                    return;
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+// parses a group after its `(`; lookarounds and flag groups do not consume text
+fn parse_group(chars: &mut Peekable<Chars>) -> Node {
+    if chars.peek() != Some(&'?') {
+        return Node::Group(parse_alternatives(chars));
+    }
+    chars.next();
+    let mut prefix = String::new();
+    while let Some(&c) = chars.peek() {
+        if c == ':' || c == ')' || c == '=' || c == '!' || c == '>' {
+            break;
+        }
+        prefix.push(c);
+        chars.next();
+    }
+    match chars.next() {
+        Some(':') => Node::Group(parse_alternatives(chars)),
+        Some('>') if prefix.starts_with('P') || prefix.starts_with('<') => Node::Group(parse_alternatives(chars)),
+        Some(')') => Node::Other, // flags such as (?s)
+        _ => {
+            parse_alternatives(chars); // lookahead/lookbehind/atomic group
+            Node::Other
+        }
+    }
+}
+
+// parses an optional quantifier
+// returns whether the previous piece is optional and whether it may repeat
+fn parse_quantifier(chars: &mut Peekable<Chars>) -> (bool, bool) {
+    let quantifier = match chars.peek() {
+        Some('?') => (true, false),
+        Some('*') => (true, true),
+        Some('+') => (false, true),
+        Some('{') => {
+            let rest: String = chars.clone().take_while(|c| *c != '}').collect();
+            let bounds = &rest[1..];
+            let is_quantifier = !bounds.is_empty() && bounds.chars().all(|c| c.is_ascii_digit() || c == ',');
+            if !is_quantifier {
+                return (false, false);
+            }
+            for _ in 0..rest.chars().count() {
+                chars.next();
+            }
+            let min = bounds.split(',').next().and_then(|min| min.parse::<usize>().ok()).unwrap_or(0);
+            chars.next(); // the closing `}`
+            if chars.peek() == Some(&'?') || chars.peek() == Some(&'+') {
+                chars.next();
+            }
+            return (min == 0, bounds != "1");
+        }
+        _ => return (false, false),
+    };
+    chars.next();
+    if chars.peek() == Some(&'?') || chars.peek() == Some(&'+') {
+        chars.next(); // lazy or possessive
+    }
+    quantifier
+}
+
+// every alternative must contribute a literal, the result is their union
+fn alternatives_literals(alternatives: &[Vec<Piece>]) -> Option<Vec<String>> {
+    let mut literals = vec![];
+    for sequence in alternatives {
+        literals.extend(sequence_literals(sequence)?);
+    }
+    literals.sort();
+    literals.dedup();
+    Some(literals)
+}
+
+// picks the best set of literals required by a sequence of pieces:
+// a run of mandatory characters, or the literals of a mandatory group
+fn sequence_literals(sequence: &[Piece]) -> Option<Vec<String>> {
+    let mut candidates: Vec<Vec<String>> = vec![];
+    let mut run = String::new();
+    for piece in sequence {
+        match (&piece.node, piece.optional) {
+            (Node::Literal(c), false) => {
+                run.push(*c);
+                if piece.repeated {
+                    candidates.push(vec![std::mem::take(&mut run)]);
+                }
+            }
+            (Node::Group(alternatives), false) => {
+                if !run.is_empty() {
+                    candidates.push(vec![std::mem::take(&mut run)]);
+                }
+                if let Some(literals) = alternatives_literals(alternatives) {
+                    candidates.push(literals);
+                }
+            }
+            _ => {
+                if !run.is_empty() {
+                    candidates.push(vec![std::mem::take(&mut run)]);
+                }
+            }
+        }
+    }
+    if !run.is_empty() {
+        candidates.push(vec![run]);
+    }
+    // the longest shortest literal filters best, then the fewest literals
+    candidates.into_iter()
+        .filter(|literals| !literals.is_empty())
+        .max_by_key(|literals| (literals.iter().map(|literal| literal.chars().count()).min().unwrap_or(0), usize::MAX - literals.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fancy_regex::Regex;
+
+    // checks that the literals of a pattern are found in every source it matches, and returns them
+    fn literals(pattern: &str, should_match: &[&str], should_not_match: &[&str]) -> Option<Vec<String>> {
+        let regex = Regex::new(pattern).expect("valid regex");
+        let literals = required_literals(pattern);
+        let selected = |source: &str| literals.as_ref().is_none_or(|literals| literals.iter().any(|literal| source.contains(literal.as_str())));
+        for source in should_match {
+            assert!(regex.is_match(source).unwrap(), "{} does not match {:?}", pattern, source);
+            assert!(selected(source), "the literals {:?} of {} skip {:?}", literals, pattern, source);
+        }
+        for source in should_not_match {
+            assert!(!regex.is_match(source).unwrap(), "{} matches {:?}", pattern, source);
+        }
+        literals
+    }
+
+    // inline flags combining `i` with other flags
+    #[test]
+    fn case_insensitive_flags() {
+        assert_eq!(literals("(?mi)todo", &["// TODO fix", "/* todo */"], &["// done"]), None);
+        assert_eq!(literals("(?si:unchecked)\\s*\\{", &["Unchecked { ++i; }", "unchecked { ++i; }"], &["checked { ++i; }"]), None);
+        assert_eq!(literals("(?s-i)unchecked", &["unchecked"], &[]), None);
+    }
+
+    // other flags do not prevent prefiltering
+    #[test]
+    fn other_flags() {
+        assert_eq!(literals("(?m)^\\s*selfdestruct\\(", &["    selfdestruct(owner);"], &["// destruct"]), Some(vec!["selfdestruct(".to_string()]));
+    }
+
+    // an escape is a whole character, its digits are not literals
+    #[test]
+    fn hexadecimal_escapes() {
+        assert_eq!(
+            literals("fun\\x63tion|re\\x{71}uire", &["function f() external {}", "require(ok);"], &["modifier m() { _; }"]),
+            Some(vec!["tion".to_string(), "uire".to_string()]),
+        );
+    }
+
+    #[test]
+    fn unicode_escapes_and_classes() {
+        assert_eq!(
+            literals("emit\\u{20}\\p{Lu}\\P{Lu}+\\(", &["emit Transfer(from, to, amount);"], &["emit transfer(from, to, amount);"]),
+            Some(vec!["emit".to_string()]),
+        );
+    }
+}
@@ -0,0 +1,116 @@
+use std::fs;
+use std::path::Path;
+use fancy_regex::Regex;
+use serde::Deserialize;
+use crate::regexrules::{Rule, DEFAULT_PACK};
+
+// Directory that is searched for rule packs when it exists in the working directory
+pub static DEFAULT_RULES_DIR: &str = ".auditooor/rules";
+
+// A rule pack file, in any of the supported formats
+// rules are read one by one, so that an error names the rule it is in
+#[derive(Deserialize)]
+struct RulePack {
+    #[serde(default)]
+    rules: Vec<serde_json::Value>,
+}
+
+// returns true if the file extension is one of the supported rule pack formats
+fn is_rule_pack(path: &Path) -> bool {
+    matches!(path.extension().and_then(|ext| ext.to_str()), Some("toml" | "yaml" | "yml" | "json"))
+}
+
+// parses a rule pack, picking the format from the extension of `origin`
+// returns the rules of the pack, or a description of the parse error
+pub fn parse_rule_pack(contents: &str, origin: &str) -> Result<Vec<Rule>, String> {
+    let extension = Path::new(origin).extension().and_then(|ext| ext.to_str()).unwrap_or("toml");
+    let pack: RulePack = match extension {
+        "json" => serde_json::from_str(contents).map_err(|err| err.to_string()),
+        "yaml" | "yml" => serde_yaml::from_str(contents).map_err(|err| err.to_string()),
+        _ => toml::from_str(contents).map_err(|err| err.to_string()),
+    }.map_err(|err| format!("{}: failed to parse rule pack: {}", origin, err))?;
+    pack.rules.into_iter().enumerate().map(|(index, rule)| {
+        let id = match rule.get("id").and_then(|id| id.as_str()) {
+            Some(id) => id.to_string(),
+            None => format!("#{}", index + 1),
+        };
+        Rule::deserialize(rule).map_err(|err| format!("{}: rule {} is invalid: {}", origin, id, err))
+    }).collect()
+}
+
+// checks that every rule of a pack can be used
+// returns one message per broken rule
+pub fn validate(rules: &[Rule], origin: &str) -> Vec<String> {
+    let mut errors = vec![];
+    for rule in rules {
+        if rule.id.trim().is_empty() {
+            errors.push(format!("{}: rule \"{}\" has no id", origin, rule.title));
+        } else if rule.regex.is_empty() {
+            errors.push(format!("{}: rule {} has an empty regex", origin, rule.id));
+        } else if let Err(err) = Regex::new(&rule.regex) {
+            errors.push(format!("{}: rule {} has an invalid regex: {}", origin, rule.id, err));
+        }
+    }
+    errors
+}
+
+// loads and validates a single rule pack file
+pub fn load_rule_pack(path: &Path) -> Result<Vec<Rule>, Vec<String>> {
+    let origin = path.display().to_string();
+    let contents = fs::read_to_string(path).map_err(|err| vec![format!("{}: failed to read rule pack: {}", origin, err)])?;
+    let rules = parse_rule_pack(&contents, &origin).map_err(|err| vec![err])?;
+    let errors = validate(&rules, &origin);
+    if errors.is_empty() { Ok(rules) } else { Err(errors) }
+}
+
+// loads every rule pack of a directory, in file name order
+pub fn load_rules_dir(dir: &Path) -> Result<Vec<Rule>, Vec<String>> {
+    let entries = fs::read_dir(dir).map_err(|err| vec![format!("{}: failed to read rules directory: {}", dir.display(), err)])?;
+    let mut paths: Vec<_> = entries.filter_map(|entry| entry.ok()).map(|entry| entry.path())
+        .filter(|path| path.is_file() && is_rule_pack(path))
+        .collect();
+    paths.sort();
+
+    let mut rules = vec![];
+    let mut errors = vec![];
+    for path in paths {
+        match load_rule_pack(&path) {
+            Ok(mut pack) => rules.append(&mut pack),
+            Err(mut pack_errors) => errors.append(&mut pack_errors),
+        }
+    }
+    if errors.is_empty() { Ok(rules) } else { Err(errors) }
+}
+
+// returns the built-in rules
+pub fn default_rules() -> Result<Vec<Rule>, Vec<String>> {
+    let rules = parse_rule_pack(DEFAULT_PACK, "default.toml").map_err(|err| vec![err])?;
+    let errors = validate(&rules, "default.toml");
+    if errors.is_empty() { Ok(rules) } else { Err(errors) }
+}
+
+// loads the built-in pack (unless disabled), the default rules directory,
+// the given rule directories and the given rule pack files, in that order
+// returns every rule, or every problem found while loading
+pub fn load_rules(use_defaults: bool, rules_dirs: &[impl AsRef<Path>], rule_files: &[impl AsRef<Path>]) -> Result<Vec<Rule>, Vec<String>> {
+    let mut rules = vec![];
+    let mut errors = vec![];
+    let mut collect = |result: Result<Vec<Rule>, Vec<String>>| match result {
+        Ok(mut pack) => rules.append(&mut pack),
+        Err(mut pack_errors) => errors.append(&mut pack_errors),
+    };
+
+    if use_defaults {
+        collect(default_rules());
+    }
+    if Path::new(DEFAULT_RULES_DIR).is_dir() {
+        collect(load_rules_dir(Path::new(DEFAULT_RULES_DIR)));
+    }
+    for dir in rules_dirs {
+        collect(load_rules_dir(dir.as_ref()));
+    }
+    for file in rule_files {
+        collect(load_rule_pack(file.as_ref()));
+    }
+    if errors.is_empty() { Ok(rules) } else { Err(errors) }
+}
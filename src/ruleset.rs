@@ -0,0 +1,93 @@
+use aho_corasick::AhoCorasick;
+use fancy_regex::Regex;
+use crate::detectors::ConfiguredDetector;
+use crate::prefilter;
+use crate::regexrules::Rule;
+
+// The rules of a scan with their regexes compiled once, shared by every file
+// rules with required literals are only evaluated on files containing one of
+// them, found with a single multi-pattern pass over the file
+// AST detectors run alongside the regex rules on every parsed file
+pub struct RuleSet {
+    rules: Vec<Rule>,
+    // every detector with its rule, as configured for the project
+    detectors: Vec<ConfiguredDetector>,
+    regexes: Vec<Regex>,
+    // indexes into the automaton patterns, None when the rule always runs
+    literals: Vec<Option<Vec<usize>>>,
+    automaton: Option<AhoCorasick>,
+    patterns: Vec<String>,
+}
+
+impl RuleSet {
+    // compiles the regex of every rule and the literal prefilter
+    // returns one message per rule whose regex does not compile or whose id is taken by a detector
+    pub fn compile(rules: Vec<Rule>, detectors: Vec<ConfiguredDetector>) -> Result<RuleSet, Vec<String>> {
+        let mut regexes = vec![];
+        let mut errors = vec![];
+        for (detector_rule, _) in &detectors {
+            if rules.iter().any(|rule| rule.id == detector_rule.id) {
+                errors.push(format!("rule {} is already defined by a built-in detector", detector_rule.id));
+            }
+        }
+        let mut literals = vec![];
+        let mut patterns: Vec<String> = vec![];
+        for rule in &rules {
+            match Regex::new(&rule.regex) {
+                Ok(regex) => regexes.push(regex),
+                Err(err) => errors.push(format!("rule {} has an invalid regex: {}", rule.id, err)),
+            }
+            literals.push(prefilter::required_literals(&rule.regex).map(|rule_literals| {
+                rule_literals.into_iter().map(|literal| match patterns.iter().position(|pattern| *pattern == literal) {
+                    Some(index) => index,
+                    None => {
+                        patterns.push(literal);
+                        patterns.len() - 1
+                    }
+                }).collect()
+            }));
+        }
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+        let automaton = if patterns.is_empty() {
+            None
+        } else {
+            Some(AhoCorasick::new(&patterns).map_err(|err| vec![format!("failed to build the literal prefilter: {}", err)])?)
+        };
+        Ok(RuleSet { rules, detectors, regexes, literals, automaton, patterns })
+    }
+
+    // returns the regex rules, then the rules of the detectors
+    pub fn rules(&self) -> impl Iterator<Item = &Rule> {
+        self.rules.iter().chain(self.detectors.iter().map(|(rule, _)| rule))
+    }
+
+    pub fn detectors(&self) -> &[ConfiguredDetector] {
+        &self.detectors
+    }
+
+    // returns the rules worth evaluating on a file with their compiled regex, in rule order
+    pub fn candidates(&self, source: &str) -> impl Iterator<Item = (&Rule, &Regex)> {
+        let mut found = vec![false; self.patterns.len()];
+        if let Some(automaton) = &self.automaton {
+            let mut remaining = self.patterns.len();
+            for m in automaton.find_overlapping_iter(source) {
+                let seen = &mut found[m.pattern().as_usize()];
+                if !*seen {
+                    *seen = true;
+                    remaining -= 1;
+                    if remaining == 0 {
+                        break;
+                    }
+                }
+            }
+        }
+        self.rules.iter().zip(&self.regexes).zip(&self.literals)
+            .filter(move |(_, literals)| match literals {
+                Some(literals) => literals.iter().any(|index| found[*index]),
+                None => true,
+            })
+            .map(|(rule_and_regex, _)| rule_and_regex)
+    }
+}
@@ -0,0 +1,147 @@
+use std::fs::File;
+use std::path::Path;
+use serde_json::{json, Value};
+use crate::finding::Finding;
+use crate::regexrules::{Rule, Severity};
+
+// Name of the SARIF log written into the output directory
+pub static REPORT_NAME: &str = "auditooor.sarif";
+
+static SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+// SARIF level used for the results of a severity
+fn level(severity: Severity) -> &'static str {
+    match severity {
+        Severity::High | Severity::Medium => "error",
+        Severity::Low => "warning",
+        Severity::NonCritical | Severity::Gas | Severity::Informational => "note",
+    }
+}
+
+// maps a rule to a SARIF `reportingDescriptor`
+fn reporting_descriptor(rule: &Rule) -> Value {
+    let mut descriptor = json!({
+        "id": rule.id,
+        "name": rule.id,
+        "shortDescription": { "text": rule.title },
+        "fullDescription": { "text": if rule.description.is_empty() { &rule.title } else { &rule.description } },
+        "help": { "text": rule.recommendation, "markdown": rule.recommendation },
+        "defaultConfiguration": { "level": level(rule.severity) },
+        "properties": { "category": rule.severity.name(), "tags": rule.tags },
+    });
+    if let Some(reference) = rule.references.first() {
+        descriptor["helpUri"] = json!(reference);
+    }
+    descriptor
+}
+
+// returns the SARIF artifact location of a scanned file
+// relative paths are resolved against the `%SRCROOT%` base
+fn artifact_location(file: &str) -> Value {
+    let uri = file.replace('\\', "/");
+    if Path::new(file).is_absolute() {
+        let uri = if uri.starts_with('/') { format!("file://{}", uri) } else { format!("file:///{}", uri) };
+        json!({ "uri": uri })
+    } else {
+        json!({ "uri": uri.trim_start_matches("./"), "uriBaseId": "%SRCROOT%" })
+    }
+}
+
+// returns the SARIF region of a finding, columns are counted in code points
+fn region(finding: &Finding) -> Value {
+    json!({
+        "startLine": finding.start_line,
+        "startColumn": finding.start_column,
+        "endLine": finding.end_line,
+        "endColumn": finding.end_column,
+        "byteOffset": finding.start,
+        "byteLength": finding.end - finding.start,
+        "snippet": { "text": finding.snippet },
+    })
+}
+
+// returns the SARIF related locations of a finding, numbered from 1
+fn related_locations(finding: &Finding) -> Vec<Value> {
+    finding.related.iter().enumerate().map(|(index, related)| json!({
+        "id": index + 1,
+        "message": { "text": related.message },
+        "physicalLocation": {
+            "artifactLocation": artifact_location(&finding.file),
+            "region": {
+                "startLine": related.start_line,
+                "startColumn": related.start_column,
+                "endLine": related.end_line,
+                "endColumn": related.end_column,
+                "byteOffset": related.start,
+                "byteLength": related.end - related.start,
+                "snippet": { "text": related.snippet },
+            },
+        },
+    })).collect()
+}
+
+// returns the SARIF logical locations of a finding: its function, or its contract
+fn logical_locations(finding: &Finding) -> Value {
+    match (&finding.function, finding.scope()) {
+        (Some(function), Some(scope)) => json!([{ "name": function, "fullyQualifiedName": scope, "kind": "function" }]),
+        (None, Some(scope)) => json!([{ "name": scope, "fullyQualifiedName": scope, "kind": "type" }]),
+        _ => json!([]),
+    }
+}
+
+// writes the rules that ran and their findings as a SARIF 2.1.0 log
+// suppressed findings are included with an `inSource` suppression,
+// and findings are marked as `new` when compared with a baseline
+pub fn write_sarif(path: &Path, rules: &[&Rule], findings: &[Finding], suppressed: &[Finding], with_baseline: bool) -> Result<(), String> {
+    let descriptors: Vec<Value> = rules.iter().map(|rule| reporting_descriptor(rule)).collect();
+
+    let mut sarif_results = vec![];
+    for (finding, is_suppressed) in findings.iter().map(|finding| (finding, false)).chain(suppressed.iter().map(|finding| (finding, true))) {
+        let rule_index = rules.iter().position(|rule| rule.id == finding.rule_id);
+        let message = rule_index.map(|index| rules[index].title.as_str()).unwrap_or(finding.rule_id.as_str());
+        let mut sarif_result = json!({
+            "ruleId": finding.rule_id,
+            "level": level(finding.severity),
+            "message": { "text": message },
+            "locations": [{
+                "physicalLocation": {
+                    "artifactLocation": artifact_location(&finding.file),
+                    "region": region(finding),
+                },
+                "logicalLocations": logical_locations(finding),
+            }],
+        });
+        if let Some(index) = rule_index {
+            sarif_result["ruleIndex"] = json!(index);
+        }
+        if !finding.related.is_empty() {
+            sarif_result["relatedLocations"] = json!(related_locations(finding));
+        }
+        if with_baseline && !is_suppressed {
+            sarif_result["baselineState"] = json!("new");
+        }
+        if is_suppressed {
+            sarif_result["suppressions"] = json!([{ "kind": "inSource" }]);
+        }
+        sarif_results.push(sarif_result);
+    }
+
+    let log = json!({
+        "$schema": SARIF_SCHEMA,
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": env!("CARGO_PKG_NAME"),
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "informationUri": "https://github.com/svskaushik/Auditooor",
+                    "rules": descriptors,
+                }
+            },
+            "columnKind": "unicodeCodePoints",
+            "results": sarif_results,
+        }]
+    });
+    let file = File::create(path).map_err(|err| format!("(write_sarif) - It was not possible to create {}: {}", path.display(), err))?;
+    serde_json::to_writer_pretty(file, &log).map_err(|err| format!("(write_sarif) - Write failed: {}", err))
+}
@@ -0,0 +1,122 @@
+use std::fs;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use crate::diff::{self, ChangedLines};
+use crate::discovery::SourceFile;
+use crate::finding::{line_and_column, Finding, Related};
+use crate::ruleset::RuleSet;
+use crate::solidity;
+use crate::suppression::Suppressions;
+use crate::tokenizer::SourceMap;
+
+// Findings of one file, split by whether a suppression comment silences them,
+// and the rules that failed while matching the file
+// `analyzed` is false for files left out of the analysis, e.g. unchanged with --diff
+// `unchanged` are the findings left out by --diff for being on unchanged lines: not reported, yet not fixed
+#[derive(Clone, Default)]
+pub struct FileFindings {
+    pub findings: Vec<Finding>,
+    pub suppressed: Vec<Finding>,
+    pub unchanged: Vec<Finding>,
+    pub warnings: Vec<String>,
+    pub analyzed: bool,
+}
+
+// Findings of one file, or why it could not be analyzed
+pub type FileResult = Result<FileFindings, String>;
+
+// analyze the code using rules
+// reads the file once, see `analyzing_source`
+// with `changes`, files without changed lines are not read
+pub fn analyzing(file: &SourceFile, rule_set: &RuleSet, changes: Option<&ChangedLines>) -> FileResult{
+
+    if changes.is_some_and(|changes| changes.ranges(&file.relative).is_none()) {
+        return Ok(FileFindings::default()); // the file did not change
+    }
+    let source = fs::read_to_string(&file.path).map_err(|err| format!("(analyzing) - Failed to open {}: {}", file.relative, err))?;
+    Ok(analyzing_source(&file.relative, &source, rule_set, changes))
+}
+
+// runs the rules whose literals appear in the source, then parses it for the detectors
+// returns the findings of every regex rule, in rule order, then those of the detectors,
+// each attributed to its enclosing contract and function,
+// with the findings silenced by suppression comments set apart
+// with `changes`, only the findings on changed lines are reported, the others are set apart as `unchanged`
+pub fn analyzing_source(relative: &str, source: &str, rule_set: &RuleSet, changes: Option<&ChangedLines>) -> FileFindings {
+
+    let changed_ranges = changes.map(|changes| changes.ranges(relative));
+    let mut findings: Vec<Finding> = vec![];
+    let mut warnings = vec![];
+    let source_map = SourceMap::new(source);
+
+    for (rule, re) in rule_set.candidates(source) {
+        for m in re.find_iter(source) {
+            // e.g. the backtrack limit was hit, the rest of the file is not matched
+            let m = match m {
+                Ok(m) => m,
+                Err(err) => {
+                    warnings.push(format!("(analyzing) - Rule {} stopped matching {}: {}", rule.id, relative, err));
+                    break;
+                }
+            };
+            //Removing matches in comments/strings, unless the rule allows them
+            let leading_whitespace = m.as_str().len() - m.as_str().trim_start().len();
+            if rule.match_in.contains(&source_map.region_at(m.start() + leading_whitespace)){
+                findings.push(Finding::new(&rule.id, rule.severity, relative, source, m.start(), m.end()));
+            }
+        }
+    }
+
+    let (unit, _) = solidity::parse(source);
+    for (rule, detector) in rule_set.detectors() {
+        for detection in detector.detect(source, &unit) {
+            let mut finding = Finding::new(&rule.id, rule.severity, relative, source, detection.span.start, detection.span.end);
+            finding.related = detection.related.iter().map(|(span, message)| Related::new(message, source, span.start, span.end)).collect();
+            findings.push(finding);
+        }
+    }
+
+    for finding in &mut findings {
+        let (contract, function) = unit.enclosing(finding.anchor());
+        finding.contract = contract.map(|contract| contract.name.clone());
+        finding.function = function.map(|function| function.display_name());
+    }
+
+    let mut unchanged = vec![];
+    if let (Some(Some(ranges)), Some(changes)) = (changed_ranges, changes) {
+        (findings, unchanged) = findings.into_iter().partition(|finding| {
+            let in_changed_function = || unit.enclosing(finding.anchor()).1.is_some_and(|function| {
+                diff::touches(ranges, line_and_column(source, function.span.start).0, line_and_column(source, function.span.end).0)
+            });
+            diff::touches(ranges, finding.start_line, finding.end_line) || (changes.whole_functions && in_changed_function())
+        });
+    }
+
+    let suppressions = Suppressions::new(source, &source_map);
+    unchanged.retain(|finding| !suppressions.suppresses(finding));
+    let (suppressed, findings) = findings.into_iter().partition(|finding| suppressions.suppresses(finding));
+    FileFindings { findings, suppressed, unchanged, warnings, analyzed: true }
+}
+
+// analyzes the files on a pool of `jobs` worker threads
+// returns the result of every file, in the same order as `files`
+pub fn scan_files(files: &[SourceFile], rule_set: &RuleSet, changes: Option<&ChangedLines>, jobs: usize) -> Vec<FileResult> {
+    let next_file = AtomicUsize::new(0);
+    let results: Vec<Mutex<Option<FileResult>>> = files.iter().map(|_| Mutex::new(None)).collect();
+
+    thread::scope(|scope| {
+        for _ in 0..jobs.clamp(1, files.len().max(1)) {
+            scope.spawn(|| loop {
+                let index = next_file.fetch_add(1, Ordering::Relaxed);
+                let Some(file) = files.get(index) else { break };
+                let result = analyzing(file, rule_set, changes);
+                *results[index].lock().expect("(scan_files) - poisoned result") = Some(result);
+            });
+        }
+    });
+
+    results.into_iter()
+        .map(|result| result.into_inner().expect("(scan_files) - poisoned result").expect("(scan_files) - file was not analyzed"))
+        .collect()
+}
@@ -0,0 +1,140 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use globset::{Glob, GlobBuilder, GlobSet, GlobSetBuilder};
+use serde::Serialize;
+use crate::discovery::SourceFile;
+use crate::tokenizer::SourceMap;
+
+// The in-scope files of an audit, read from a `--scope` file
+pub struct Scope {
+    pub source: PathBuf,
+    pub entries: Vec<String>,
+    globs: Vec<Glob>,
+    set: GlobSet,
+}
+
+impl Scope {
+    // returns true if a file is in scope
+    // entries are relative to the audited repository, so they are matched
+    // against the report path and every suffix of it
+    pub fn contains(&self, relative: &str) -> bool {
+        suffixes(relative).any(|suffix| self.set.is_match(suffix))
+    }
+
+    // returns the entries that did not select any of the given files
+    pub fn unmatched(&self, files: &[SourceFile]) -> Vec<&str> {
+        self.globs.iter().zip(&self.entries)
+            .filter(|(glob, _)| {
+                let matcher = glob.compile_matcher();
+                !files.iter().any(|file| suffixes(&file.relative).any(|suffix| matcher.is_match(suffix)))
+            })
+            .map(|(_, entry)| entry.as_str())
+            .collect()
+    }
+}
+
+// returns `a/b/c.sol`, `b/c.sol` and `c.sol` for `a/b/c.sol`
+fn suffixes(relative: &str) -> impl Iterator<Item = &str> {
+    std::iter::once(relative).chain(relative.match_indices('/').map(move |(i, _)| &relative[i + 1..]))
+}
+
+// extracts the in-scope paths/globs of a scope file
+// accepts one path or glob per line (`#` comments and `-`/`*` bullets are
+// allowed) and the contest README table format, e.g.
+// `| [src/Vault.sol](src/Vault.sol) | 120 | Vault logic |`
+pub fn parse_scope(contents: &str) -> Vec<String> {
+    let mut entries = vec![];
+    for line in contents.lines() {
+        let line = line.trim();
+        let entry = if line.starts_with('|') {
+            let first_cell = line.trim_matches('|').split('|').next().unwrap_or("").trim();
+            link_text(first_cell)
+        } else if line.is_empty() || line.starts_with('#') {
+            continue;
+        } else {
+            link_text(line.trim_start_matches(['-', '*', '+']).trim_start())
+        };
+        let entry = entry.trim_matches('`').trim().trim_start_matches("./");
+
+        let is_path = entry.ends_with(".sol") || entry.contains('*') || entry.ends_with('/');
+        if line.starts_with('|') && !is_path {
+            continue; // header, separator or summary row of a table
+        }
+        if entry.ends_with('/') {
+            entries.push(format!("{}**", entry));
+        } else if !entry.is_empty() {
+            entries.push(entry.to_string());
+        }
+    }
+    entries
+}
+
+// returns the text of a markdown link `[text](url)`, or the input itself
+fn link_text(cell: &str) -> &str {
+    match (cell.strip_prefix('['), cell.find("](")) {
+        (Some(_), Some(end)) => &cell[1..end],
+        _ => cell,
+    }
+}
+
+// reads a scope file
+// returns the scope, or an error if it can not be read or holds a bad glob
+pub fn load_scope(path: &Path) -> Result<Scope, String> {
+    let contents = fs::read_to_string(path).map_err(|err| format!("(load_scope) - Failed to read {}: {}", path.display(), err))?;
+    let entries = parse_scope(&contents);
+    if entries.is_empty() {
+        return Err(format!("(load_scope) - {} does not list any file", path.display()));
+    }
+
+    let mut globs = vec![];
+    let mut builder = GlobSetBuilder::new();
+    for entry in &entries {
+        let glob = GlobBuilder::new(entry).literal_separator(true).build()
+            .map_err(|err| format!("(load_scope) - Invalid scope entry {} in {}: {}", entry, path.display(), err))?;
+        builder.add(glob.clone());
+        globs.push(glob);
+    }
+    let set = builder.build().map_err(|err| format!("(load_scope) - {}: {}", path.display(), err))?;
+    Ok(Scope { source: path.to_path_buf(), entries, globs, set })
+}
+
+// The in-scope files that were analyzed, written at the top of the reports
+#[derive(Clone, Debug, Serialize)]
+pub struct ScopeSummary {
+    pub scope_file: String,
+    pub file_count: usize,
+    pub total_nsloc: usize,
+    pub files: Vec<ScopedFile>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ScopedFile {
+    pub file: String,
+    pub nsloc: usize,
+}
+
+impl ScopeSummary {
+    // counts the nSLOC of every analyzed file
+    pub fn new(scope: &Scope, files: &[SourceFile]) -> ScopeSummary {
+        let files: Vec<ScopedFile> = files.iter().map(|file| {
+            let source = fs::read_to_string(&file.path).unwrap_or_default();
+            ScopedFile { file: file.relative.clone(), nsloc: SourceMap::new(&source).nsloc(&source) }
+        }).collect();
+        ScopeSummary {
+            scope_file: scope.source.display().to_string(),
+            file_count: files.len(),
+            total_nsloc: files.iter().map(|file| file.nsloc).sum(),
+            files,
+        }
+    }
+
+    // returns the scope section written at the top of the markdown reports
+    pub fn markdown_header(&self) -> String {
+        let mut header = format!("# Scope\n\nScope file: `{}`, {} files, {} nSLOC\n\n| File | nSLOC |\n| --- | --- |\n", self.scope_file, self.file_count, self.total_nsloc);
+        for file in &self.files {
+            header.push_str(&format!("| {} | {} |\n", file.file, file.nsloc));
+        }
+        header.push_str(&format!("| **Total** | **{}** |\n\n", self.total_nsloc));
+        header
+    }
+}
@@ -0,0 +1,71 @@
+use crate::detectors::ConfiguredDetector;
+use crate::regexrules::Rule;
+use crate::ruleset::RuleSet;
+use crate::scanner::analyzing_source;
+
+// Outcome of running the snippets of a rule
+pub struct RuleTest {
+    pub rule: Rule,
+    pub failures: Vec<String>,
+}
+
+impl RuleTest {
+    pub fn snippets(&self) -> usize {
+        self.rule.should_match.len() + self.rule.should_not_match.len()
+    }
+}
+
+// returns the ids used by more than one rule, in id order
+pub fn duplicate_ids<'a>(rules: impl Iterator<Item = &'a Rule>) -> Vec<&'a str> {
+    let mut ids: Vec<&str> = rules.map(|rule| rule.id.as_str()).collect();
+    ids.sort();
+    let mut duplicates: Vec<&str> = ids.windows(2).filter(|pair| pair[0] == pair[1]).map(|pair| pair[0]).collect();
+    duplicates.dedup();
+    duplicates
+}
+
+// runs the snippets of every rule through the analysis, each rule on its own
+// so that rules sharing an id do not see each other's findings
+// returns one outcome per rule, in rule order, or why a rule could not be compiled
+pub fn test_rules(rules: Vec<Rule>, detectors: Vec<ConfiguredDetector>) -> Result<Vec<RuleTest>, Vec<String>> {
+    let rule_sets = rules.into_iter().map(|rule| RuleSet::compile(vec![rule], vec![]))
+        .chain(detectors.into_iter().map(|detector| RuleSet::compile(vec![], vec![detector])));
+    let mut tests = vec![];
+    let mut errors = vec![];
+    for rule_set in rule_sets {
+        match rule_set {
+            Ok(rule_set) => tests.push(test_rule(&rule_set)),
+            Err(mut rule_errors) => errors.append(&mut rule_errors),
+        }
+    }
+    if errors.is_empty() { Ok(tests) } else { Err(errors) }
+}
+
+// checks that every `should_match` snippet has a finding and no `should_not_match` snippet has one
+fn test_rule(rule_set: &RuleSet) -> RuleTest {
+    let rule = rule_set.rules().next().expect("(test_rule) - a rule set of one rule").clone();
+    let mut failures = vec![];
+    // a rule failing at runtime on a snippet is a failure as well
+    let findings = |snippet: &str, failures: &mut Vec<String>| {
+        let file_findings = analyzing_source("snippet.sol", snippet, rule_set, None);
+        failures.extend(file_findings.warnings);
+        file_findings.findings.len()
+    };
+    for (index, snippet) in rule.should_match.iter().enumerate() {
+        if findings(snippet, &mut failures) == 0 {
+            failures.push(format!("should_match #{} has no finding:\n{}", index + 1, indent(snippet)));
+        }
+    }
+    for (index, snippet) in rule.should_not_match.iter().enumerate() {
+        let count = findings(snippet, &mut failures);
+        if count > 0 {
+            failures.push(format!("should_not_match #{} has {} finding(s):\n{}", index + 1, count, indent(snippet)));
+        }
+    }
+    RuleTest { rule, failures }
+}
+
+// indents every line of a snippet for the terminal output
+fn indent(snippet: &str) -> String {
+    snippet.lines().map(|line| format!("    {}", line)).collect::<Vec<_>>().join("\n")
+}
@@ -0,0 +1,411 @@
+// Byte span of a node in the source file
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize) -> Span {
+        Span { start, end }
+    }
+
+    // returns the text of the span
+    pub fn text<'a>(&self, source: &'a str) -> &'a str {
+        &source[self.start..self.end]
+    }
+}
+
+// A parsed Solidity file
+#[derive(Clone, Debug, Default)]
+pub struct SourceUnit {
+    pub contracts: Vec<Contract>,
+    // functions declared at file level
+    pub functions: Vec<Function>,
+}
+
+impl SourceUnit {
+    // returns the contract and the function or modifier enclosing a byte offset
+    pub fn enclosing(&self, offset: usize) -> (Option<&Contract>, Option<&Function>) {
+        let contains = |span: &Span| span.start <= offset && offset < span.end;
+        match self.contracts.iter().find(|contract| contains(&contract.span)) {
+            Some(contract) => (Some(contract), contract.functions.iter().chain(&contract.modifiers).find(|function| contains(&function.span))),
+            None => (None, self.functions.iter().find(|function| contains(&function.span))),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ContractKind {
+    Contract,
+    AbstractContract,
+    Interface,
+    Library,
+}
+
+// A contract, interface or library
+#[derive(Clone, Debug)]
+pub struct Contract {
+    pub kind: ContractKind,
+    pub name: String,
+    pub bases: Vec<InheritanceSpecifier>,
+    pub state_variables: Vec<StateVariable>,
+    pub functions: Vec<Function>,
+    pub modifiers: Vec<Function>,
+    // names of the structs, enums, events and errors it declares
+    pub types: Vec<String>,
+    pub span: Span,
+}
+
+#[derive(Clone, Debug)]
+pub struct InheritanceSpecifier {
+    pub name: String,
+    pub span: Span,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Visibility {
+    External,
+    Public,
+    Internal,
+    Private,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Mutability {
+    Pure,
+    View,
+    Payable,
+    Constant,
+    Immutable,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DataLocation {
+    Memory,
+    Storage,
+    Calldata,
+}
+
+#[derive(Clone, Debug)]
+pub struct StateVariable {
+    pub type_name: TypeName,
+    pub name: String,
+    pub mutability: Option<Mutability>,
+    pub value: Option<Expression>,
+    pub span: Span,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FunctionKind {
+    Function,
+    Constructor,
+    Fallback,
+    Receive,
+    Modifier,
+}
+
+// A function, constructor, fallback, receive or modifier definition
+#[derive(Clone, Debug)]
+pub struct Function {
+    pub kind: FunctionKind,
+    pub name: Option<String>,
+    pub parameters: Vec<Parameter>,
+    pub returns: Vec<Parameter>,
+    pub visibility: Option<Visibility>,
+    pub mutability: Option<Mutability>,
+    pub modifiers: Vec<ModifierInvocation>,
+    pub is_virtual: bool,
+    pub is_override: bool,
+    pub body: Option<Block>,
+    // from the keyword to the body or the closing `;`
+    pub header: Span,
+    pub span: Span,
+}
+
+impl Function {
+    // name used in findings, e.g. `withdraw()`, `constructor()` or `onlyOwner` for a modifier
+    pub fn display_name(&self) -> String {
+        match (&self.name, self.kind) {
+            (Some(name), FunctionKind::Modifier) => name.clone(),
+            (Some(name), _) => format!("{}()", name),
+            (None, FunctionKind::Constructor) => "constructor()".to_string(),
+            (None, FunctionKind::Receive) => "receive()".to_string(),
+            (None, _) => "fallback()".to_string(),
+        }
+    }
+
+    // whether it can be called from outside the contract
+    pub fn is_external(&self) -> bool {
+        matches!(self.visibility, Some(Visibility::External) | Some(Visibility::Public))
+            || matches!(self.kind, FunctionKind::Fallback | FunctionKind::Receive)
+    }
+
+    pub fn has_modifier(&self, name: &str) -> bool {
+        self.modifiers.iter().any(|modifier| modifier.name == name)
+    }
+}
+
+// A modifier, or a base constructor call, in a function header
+#[derive(Clone, Debug)]
+pub struct ModifierInvocation {
+    pub name: String,
+    pub arguments: Vec<Expression>,
+}
+
+// A function parameter, return value or local variable declaration
+#[derive(Clone, Debug)]
+pub struct Parameter {
+    pub location: Option<DataLocation>,
+    pub name: Option<String>,
+    pub span: Span,
+}
+
+// The kind of a type, the detectors do not need its details
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TypeName {
+    // `uint256`, `address payable`, `bytes32`...
+    Elementary,
+    // `IERC20`, `Lib.Struct`...
+    UserDefined,
+    Mapping,
+    Array,
+    Function,
+}
+
+#[derive(Clone, Debug)]
+pub struct Block {
+    pub statements: Vec<Statement>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Statement {
+    pub kind: StatementKind,
+}
+
+#[derive(Clone, Debug)]
+pub enum StatementKind {
+    Block(Block),
+    // `(uint a, , bool b) = ...` has empty slots
+    VariableDeclaration(Vec<Option<Parameter>>, Option<Expression>),
+    Expression(Expression),
+    If(Expression, Box<Statement>, Option<Box<Statement>>),
+    For(Option<Box<Statement>>, Option<Expression>, Option<Expression>, Box<Statement>),
+    While(Expression, Box<Statement>),
+    DoWhile(Box<Statement>, Expression),
+    Return(Option<Expression>),
+    Emit(Expression),
+    Revert(Expression),
+    Try(Expression, Vec<Parameter>, Block, Vec<Block>),
+    // inline assembly is kept as text
+    Assembly,
+    Break,
+    Continue,
+    // `_;` in a modifier
+    Placeholder,
+}
+
+#[derive(Clone, Debug)]
+pub struct Expression {
+    pub kind: ExpressionKind,
+    pub span: Span,
+}
+
+#[derive(Clone, Debug)]
+pub enum ExpressionKind {
+    Identifier(String),
+    // numbers, strings, `true`/`false`, with their source text
+    Literal(String),
+    // `uint256`, `address`, `payable`... used as a conversion or in `type(...)`
+    ElementaryType,
+    Member(Box<Expression>, String),
+    Index(Box<Expression>, Option<Box<Expression>>),
+    // `a[start:end]`
+    Slice(Box<Expression>, Option<Box<Expression>>, Option<Box<Expression>>),
+    // callee and arguments, named arguments are kept in order
+    Call(Box<Expression>, Vec<Expression>),
+    // `target.call{value: x}`
+    CallOptions(Box<Expression>, Vec<(String, Expression)>),
+    // operator and operand, `++x` and `x++` alike
+    Unary(String, Box<Expression>),
+    Binary(String, Box<Expression>, Box<Expression>),
+    // `=`, `+=`...
+    Assign(String, Box<Expression>, Box<Expression>),
+    Conditional(Box<Expression>, Box<Expression>, Box<Expression>),
+    Tuple(Vec<Option<Expression>>),
+    Array(Vec<Expression>),
+    // `new T`, called with the constructor arguments
+    New,
+}
+
+impl Expression {
+    // returns the variable an lvalue ultimately refers to, e.g. `balances` for `balances[a].b`
+    pub fn root_identifier(&self) -> Option<&str> {
+        match &self.kind {
+            ExpressionKind::Identifier(name) => Some(name),
+            ExpressionKind::Member(base, _) | ExpressionKind::Index(base, _) | ExpressionKind::Slice(base, ..) => base.root_identifier(),
+            _ => None,
+        }
+    }
+
+    // returns the member name if the expression is `<base>.<member>`
+    pub fn member_name(&self) -> Option<&str> {
+        match &self.kind {
+            ExpressionKind::Member(_, member) => Some(member),
+            _ => None,
+        }
+    }
+}
+
+// Walks the AST, every method defaults to visiting the children of the node
+pub trait Visitor {
+    fn visit_contract(&mut self, contract: &Contract) {
+        walk_contract(self, contract);
+    }
+
+    fn visit_function(&mut self, function: &Function) {
+        walk_function(self, function);
+    }
+
+    fn visit_statement(&mut self, statement: &Statement) {
+        walk_statement(self, statement);
+    }
+
+    fn visit_expression(&mut self, expression: &Expression) {
+        walk_expression(self, expression);
+    }
+}
+
+pub fn walk_source_unit<V: Visitor + ?Sized>(visitor: &mut V, unit: &SourceUnit) {
+    for contract in &unit.contracts {
+        visitor.visit_contract(contract);
+    }
+    for function in &unit.functions {
+        visitor.visit_function(function);
+    }
+}
+
+pub fn walk_contract<V: Visitor + ?Sized>(visitor: &mut V, contract: &Contract) {
+    for variable in &contract.state_variables {
+        if let Some(value) = &variable.value {
+            visitor.visit_expression(value);
+        }
+    }
+    for function in contract.modifiers.iter().chain(&contract.functions) {
+        visitor.visit_function(function);
+    }
+}
+
+pub fn walk_function<V: Visitor + ?Sized>(visitor: &mut V, function: &Function) {
+    for modifier in &function.modifiers {
+        for argument in &modifier.arguments {
+            visitor.visit_expression(argument);
+        }
+    }
+    if let Some(body) = &function.body {
+        walk_block(visitor, body);
+    }
+}
+
+pub fn walk_block<V: Visitor + ?Sized>(visitor: &mut V, block: &Block) {
+    for statement in &block.statements {
+        visitor.visit_statement(statement);
+    }
+}
+
+pub fn walk_statement<V: Visitor + ?Sized>(visitor: &mut V, statement: &Statement) {
+    match &statement.kind {
+        StatementKind::Block(block) => walk_block(visitor, block),
+        StatementKind::VariableDeclaration(_, value) | StatementKind::Return(value) => {
+            if let Some(value) = value {
+                visitor.visit_expression(value);
+            }
+        }
+        StatementKind::Expression(expression) | StatementKind::Emit(expression) | StatementKind::Revert(expression) => visitor.visit_expression(expression),
+        StatementKind::If(condition, then, otherwise) => {
+            visitor.visit_expression(condition);
+            visitor.visit_statement(then);
+            if let Some(otherwise) = otherwise {
+                visitor.visit_statement(otherwise);
+            }
+        }
+        StatementKind::For(init, condition, update, body) => {
+            if let Some(init) = init {
+                visitor.visit_statement(init);
+            }
+            if let Some(condition) = condition {
+                visitor.visit_expression(condition);
+            }
+            if let Some(update) = update {
+                visitor.visit_expression(update);
+            }
+            visitor.visit_statement(body);
+        }
+        StatementKind::While(condition, body) => {
+            visitor.visit_expression(condition);
+            visitor.visit_statement(body);
+        }
+        StatementKind::DoWhile(body, condition) => {
+            visitor.visit_statement(body);
+            visitor.visit_expression(condition);
+        }
+        StatementKind::Try(expression, _, body, catches) => {
+            visitor.visit_expression(expression);
+            walk_block(visitor, body);
+            for catch in catches {
+                walk_block(visitor, catch);
+            }
+        }
+        StatementKind::Assembly | StatementKind::Break | StatementKind::Continue | StatementKind::Placeholder => {}
+    }
+}
+
+pub fn walk_expression<V: Visitor + ?Sized>(visitor: &mut V, expression: &Expression) {
+    match &expression.kind {
+        ExpressionKind::Identifier(_) | ExpressionKind::Literal(_) | ExpressionKind::ElementaryType | ExpressionKind::New => {}
+        ExpressionKind::Member(base, _) | ExpressionKind::Unary(_, base) => visitor.visit_expression(base),
+        ExpressionKind::Index(base, index) => {
+            visitor.visit_expression(base);
+            if let Some(index) = index {
+                visitor.visit_expression(index);
+            }
+        }
+        ExpressionKind::Slice(base, start, end) => {
+            visitor.visit_expression(base);
+            for bound in [start, end].into_iter().flatten() {
+                visitor.visit_expression(bound);
+            }
+        }
+        ExpressionKind::Call(callee, arguments) => {
+            visitor.visit_expression(callee);
+            for argument in arguments {
+                visitor.visit_expression(argument);
+            }
+        }
+        ExpressionKind::CallOptions(callee, options) => {
+            visitor.visit_expression(callee);
+            for (_, value) in options {
+                visitor.visit_expression(value);
+            }
+        }
+        ExpressionKind::Binary(_, left, right) | ExpressionKind::Assign(_, left, right) => {
+            visitor.visit_expression(left);
+            visitor.visit_expression(right);
+        }
+        ExpressionKind::Conditional(condition, then, otherwise) => {
+            visitor.visit_expression(condition);
+            visitor.visit_expression(then);
+            visitor.visit_expression(otherwise);
+        }
+        ExpressionKind::Tuple(elements) => {
+            for element in elements.iter().flatten() {
+                visitor.visit_expression(element);
+            }
+        }
+        ExpressionKind::Array(elements) => {
+            for element in elements {
+                visitor.visit_expression(element);
+            }
+        }
+    }
+}
@@ -0,0 +1,90 @@
+// Kind of a Solidity token
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TokenKind {
+    // identifiers and keywords
+    Identifier,
+    Number,
+    // string literals, including `hex"..."` and `unicode"..."`
+    String,
+    // operators and punctuation
+    Punct,
+}
+
+// A token with its byte span in the source
+#[derive(Clone, Copy, Debug)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub start: usize,
+    pub end: usize,
+}
+
+// Operators made of several characters, longest first
+static OPERATORS: [&str; 27] = [
+    ">>>=", ">>>", "<<=", ">>=", "**", "=>", "==", "!=", "<=", ">=", "&&", "||", "++", "--", "+=", "-=",
+    "*=", "/=", "%=", "|=", "&=", "^=", "<<", ">>", "->", ":=", "..",
+];
+
+// splits a Solidity file into tokens, skipping whitespace and comments
+pub fn tokenize(source: &str) -> Vec<Token> {
+    let bytes = source.as_bytes();
+    let mut tokens = vec![];
+    let mut i = 0;
+    while i < bytes.len() {
+        let c = bytes[i];
+        if c.is_ascii_whitespace() {
+            i += 1;
+            continue;
+        }
+        if c == b'/' && bytes.get(i + 1) == Some(&b'/') {
+            i = source[i..].find('\n').map(|n| i + n).unwrap_or(bytes.len());
+            continue;
+        }
+        if c == b'/' && bytes.get(i + 1) == Some(&b'*') {
+            i = source[i + 2..].find("*/").map(|n| i + 2 + n + 2).unwrap_or(bytes.len());
+            continue;
+        }
+
+        let start = i;
+        let kind = if c == b'"' || c == b'\'' {
+            i = string_end(bytes, i);
+            TokenKind::String
+        } else if c.is_ascii_digit() || (c == b'.' && bytes.get(i + 1).is_some_and(|next| next.is_ascii_digit())) {
+            while i < bytes.len() && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'_' || bytes[i] == b'.') {
+                // `1.5` is a number, `x[1].y` is not
+                if bytes[i] == b'.' && !bytes.get(i + 1).is_some_and(|next| next.is_ascii_digit()) {
+                    break;
+                }
+                i += 1;
+            }
+            TokenKind::Number
+        } else if c.is_ascii_alphabetic() || c == b'_' || c == b'$' || c >= 0x80 {
+            while i < bytes.len() && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'_' || bytes[i] == b'$' || bytes[i] >= 0x80) {
+                i += 1;
+            }
+            // hex"..." and unicode"..." literals
+            let word = &source[start..i];
+            if (word == "hex" || word == "unicode") && matches!(bytes.get(i), Some(b'"') | Some(b'\'')) {
+                i = string_end(bytes, i);
+                TokenKind::String
+            } else {
+                TokenKind::Identifier
+            }
+        } else {
+            let operator = OPERATORS.iter().find(|operator| source[i..].starts_with(*operator));
+            i += operator.map(|operator| operator.len()).unwrap_or(1);
+            TokenKind::Punct
+        };
+        tokens.push(Token { kind, start, end: i });
+    }
+    tokens
+}
+
+// returns the offset right after the string literal starting at `start`
+fn string_end(bytes: &[u8], start: usize) -> usize {
+    let quote = bytes[start];
+    let mut end = start + 1;
+    while end < bytes.len() && bytes[end] != quote && bytes[end] != b'\n' {
+        end += if bytes[end] == b'\\' { 2 } else { 1 };
+    }
+    (end + 1).min(bytes.len())
+}
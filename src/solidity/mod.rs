@@ -0,0 +1,6 @@
+// Solidity syntax: a tolerant parser producing the AST detectors walk
+pub mod ast;
+mod lexer;
+mod parser;
+
+pub use parser::parse;
@@ -0,0 +1,907 @@
+use crate::finding::line_and_column;
+use super::ast::*;
+use super::lexer::{tokenize, Token, TokenKind};
+
+type ParseResult<T> = Result<T, String>;
+
+// Words that can not start a user-defined type name
+static RESERVED: [&str; 32] = [
+    "delete", "new", "true", "false", "type", "return", "emit", "if", "else", "for", "while", "do", "break",
+    "continue", "try", "catch", "revert", "assembly", "unchecked", "memory", "storage", "calldata", "public",
+    "private", "internal", "external", "pure", "view", "constant", "immutable", "returns", "indexed",
+];
+
+// Suffixes of number literals, e.g. `1 ether` or `7 days`
+static UNITS: [&str; 9] = ["wei", "gwei", "ether", "seconds", "minutes", "hours", "days", "weeks", "years"];
+
+static ASSIGNMENT_OPERATORS: [&str; 12] = ["=", "|=", "^=", "&=", "<<=", ">>=", ">>>=", "+=", "-=", "*=", "/=", "%="];
+
+// parses a Solidity file
+// parsing never fails: declarations and statements that can not be parsed
+// are skipped and reported in `errors`, with their line
+pub fn parse(source: &str) -> (SourceUnit, Vec<String>) {
+    let mut parser = Parser { source, tokens: tokenize(source), pos: 0, errors: vec![] };
+    let unit = parser.source_unit();
+    (unit, parser.errors)
+}
+
+struct Parser<'a> {
+    source: &'a str,
+    tokens: Vec<Token>,
+    pos: usize,
+    errors: Vec<String>,
+}
+
+impl<'a> Parser<'a> {
+    // text of the token `offset` tokens ahead, or an empty string at the end of the file
+    fn peek_at(&self, offset: usize) -> &'a str {
+        self.tokens.get(self.pos + offset).map(|token| &self.source[token.start..token.end]).unwrap_or("")
+    }
+
+    fn peek(&self) -> &'a str {
+        self.peek_at(0)
+    }
+
+    fn kind(&self) -> Option<TokenKind> {
+        self.tokens.get(self.pos).map(|token| token.kind)
+    }
+
+    fn at(&self, text: &str) -> bool {
+        self.peek() == text
+    }
+
+    fn at_end(&self) -> bool {
+        self.pos >= self.tokens.len()
+    }
+
+    fn bump(&mut self) -> &'a str {
+        let text = self.peek();
+        self.pos += 1;
+        text
+    }
+
+    fn eat(&mut self, text: &str) -> bool {
+        if self.at(text) {
+            self.pos += 1;
+            return true;
+        }
+        false
+    }
+
+    fn expect(&mut self, text: &str) -> ParseResult<()> {
+        if self.eat(text) {
+            return Ok(());
+        }
+        Err(self.unexpected(&format!("`{}`", text)))
+    }
+
+    fn identifier(&mut self) -> ParseResult<String> {
+        if self.kind() == Some(TokenKind::Identifier) {
+            return Ok(self.bump().to_string());
+        }
+        Err(self.unexpected("an identifier"))
+    }
+
+    // `A.B.C`
+    fn path(&mut self) -> ParseResult<String> {
+        let mut path = self.identifier()?;
+        while self.at(".") && self.tokens.get(self.pos + 1).is_some_and(|token| token.kind == TokenKind::Identifier) {
+            self.bump();
+            path.push('.');
+            path.push_str(self.bump());
+        }
+        Ok(path)
+    }
+
+    // offset where the current token starts
+    fn start(&self) -> usize {
+        self.tokens.get(self.pos).map(|token| token.start).unwrap_or(self.source.len())
+    }
+
+    // span from `start` to the end of the last consumed token
+    fn span_from(&self, start: usize) -> Span {
+        let end = if self.pos == 0 { start } else { self.tokens[self.pos - 1].end };
+        Span::new(start, end.max(start))
+    }
+
+    fn unexpected(&self, expected: &str) -> String {
+        let (line, _) = line_and_column(self.source, self.start());
+        match self.at_end() {
+            true => format!("line {}: expected {}, found the end of the file", line, expected),
+            false => format!("line {}: expected {}, found `{}`", line, expected, self.peek()),
+        }
+    }
+
+    // skips to the end of the current declaration or statement: a `;` or a
+    // balanced `{ ... }`, without consuming the `}` closing the enclosing block
+    fn skip_item(&mut self) {
+        let mut depth: usize = 0;
+        while !self.at_end() {
+            match self.peek() {
+                "{" | "(" | "[" => depth += 1,
+                "}" if depth == 0 => return,
+                "}" | ")" | "]" => {
+                    depth = depth.saturating_sub(1);
+                    if depth == 0 && self.at("}") {
+                        self.bump();
+                        return;
+                    }
+                }
+                ";" if depth == 0 => {
+                    self.bump();
+                    return;
+                }
+                _ => {}
+            }
+            self.bump();
+        }
+    }
+
+    // skips a balanced `{ ... }` or `( ... )` starting at the current token
+    fn skip_balanced(&mut self, open: &str, close: &str) -> ParseResult<()> {
+        self.expect(open)?;
+        let mut depth = 1;
+        while depth > 0 {
+            if self.at_end() {
+                return Err(self.unexpected(&format!("`{}`", close)));
+            }
+            let token = self.bump();
+            if token == open {
+                depth += 1;
+            } else if token == close {
+                depth -= 1;
+            }
+        }
+        Ok(())
+    }
+
+    // records the error of a failed declaration or statement and skips it
+    fn recover(&mut self, error: String, from: usize) {
+        self.errors.push(error);
+        if self.pos == from && !self.at_end() && !self.at("}") {
+            self.bump();
+        }
+        self.skip_item();
+    }
+
+    fn source_unit(&mut self) -> SourceUnit {
+        let mut unit = SourceUnit::default();
+        while !self.at_end() {
+            let from = self.pos;
+            let result = match self.peek() {
+                "contract" | "interface" | "library" | "abstract" => self.contract().map(|contract| unit.contracts.push(contract)),
+                "function" => self.function().map(|function| unit.functions.push(function)),
+                "}" => {
+                    self.bump();
+                    Ok(())
+                }
+                // pragmas, imports, file-level types, constants and `using` directives
+                _ => {
+                    self.skip_item();
+                    Ok(())
+                }
+            };
+            if let Err(error) = result {
+                self.recover(error, from);
+            }
+        }
+        unit
+    }
+
+    fn contract(&mut self) -> ParseResult<Contract> {
+        let start = self.start();
+        let kind = match self.bump() {
+            "abstract" => {
+                self.expect("contract")?;
+                ContractKind::AbstractContract
+            }
+            "interface" => ContractKind::Interface,
+            "library" => ContractKind::Library,
+            _ => ContractKind::Contract,
+        };
+        let name = self.identifier()?;
+        let mut bases = vec![];
+        if self.eat("is") {
+            loop {
+                let base_start = self.start();
+                let base = self.path()?;
+                // base constructor arguments are not kept
+                if self.at("(") {
+                    self.skip_balanced("(", ")")?;
+                }
+                bases.push(InheritanceSpecifier { name: base, span: self.span_from(base_start) });
+                if !self.eat(",") {
+                    break;
+                }
+            }
+        }
+        self.expect("{")?;
+
+        let mut contract = Contract {
+            kind,
+            name,
+            bases,
+            state_variables: vec![],
+            functions: vec![],
+            modifiers: vec![],
+            types: vec![],
+            span: Span::default(),
+        };
+        while !self.at("}") && !self.at_end() {
+            let from = self.pos;
+            let result = match self.peek() {
+                "function" | "constructor" | "fallback" | "receive" => self.function().map(|function| contract.functions.push(function)),
+                "modifier" => self.function().map(|modifier| contract.modifiers.push(modifier)),
+                "struct" | "enum" | "event" | "error" => {
+                    self.bump();
+                    if self.kind() == Some(TokenKind::Identifier) {
+                        contract.types.push(self.peek().to_string());
+                    }
+                    self.skip_item();
+                    Ok(())
+                }
+                "using" | "type" => {
+                    self.skip_item();
+                    Ok(())
+                }
+                _ => self.state_variable().map(|variable| contract.state_variables.push(variable)),
+            };
+            if let Err(error) = result {
+                self.recover(error, from);
+            }
+        }
+        self.expect("}")?;
+        contract.span = self.span_from(start);
+        Ok(contract)
+    }
+
+    fn state_variable(&mut self) -> ParseResult<StateVariable> {
+        let start = self.start();
+        let type_name = self.type_name()?;
+        let mut mutability = None;
+        loop {
+            match self.peek() {
+                "constant" => mutability = Some(Mutability::Constant),
+                "immutable" => mutability = Some(Mutability::Immutable),
+                "public" | "private" | "internal" | "transient" => {}
+                "override" => {
+                    self.bump();
+                    if self.at("(") {
+                        self.skip_balanced("(", ")")?;
+                    }
+                    continue;
+                }
+                _ => break,
+            }
+            self.bump();
+        }
+        let name = self.identifier()?;
+        let value = if self.eat("=") { Some(self.expression()?) } else { None };
+        self.expect(";")?;
+        Ok(StateVariable { type_name, name, mutability, value, span: self.span_from(start) })
+    }
+
+    // functions, constructors, fallback and receive functions and modifiers
+    fn function(&mut self) -> ParseResult<Function> {
+        let start = self.start();
+        let mut kind = match self.bump() {
+            "constructor" => FunctionKind::Constructor,
+            "fallback" => FunctionKind::Fallback,
+            "receive" => FunctionKind::Receive,
+            "modifier" => FunctionKind::Modifier,
+            _ => FunctionKind::Function,
+        };
+        let mut name = None;
+        if matches!(kind, FunctionKind::Function | FunctionKind::Modifier) {
+            if self.kind() == Some(TokenKind::Identifier) {
+                name = Some(self.bump().to_string());
+            } else if kind == FunctionKind::Function {
+                // `function() external payable` before 0.6
+                kind = FunctionKind::Fallback;
+            }
+        }
+        let parameters = if self.at("(") || kind != FunctionKind::Modifier { self.parameter_list()? } else { vec![] };
+
+        let mut function = Function {
+            kind,
+            name,
+            parameters,
+            returns: vec![],
+            visibility: None,
+            mutability: None,
+            modifiers: vec![],
+            is_virtual: false,
+            is_override: false,
+            body: None,
+            header: Span::default(),
+            span: Span::default(),
+        };
+        while !self.at("{") && !self.at(";") && !self.at_end() {
+            match self.peek() {
+                "external" => function.visibility = Some(Visibility::External),
+                "public" => function.visibility = Some(Visibility::Public),
+                "internal" => function.visibility = Some(Visibility::Internal),
+                "private" => function.visibility = Some(Visibility::Private),
+                "pure" => function.mutability = Some(Mutability::Pure),
+                "view" => function.mutability = Some(Mutability::View),
+                "payable" => function.mutability = Some(Mutability::Payable),
+                "constant" => function.mutability = Some(Mutability::View),
+                "virtual" => function.is_virtual = true,
+                "override" => {
+                    self.bump();
+                    function.is_override = true;
+                    if self.at("(") {
+                        self.skip_balanced("(", ")")?;
+                    }
+                    continue;
+                }
+                "returns" => {
+                    self.bump();
+                    function.returns = self.parameter_list()?;
+                    continue;
+                }
+                _ => {
+                    let modifier = self.path()?;
+                    let arguments = if self.at("(") { self.call_arguments()? } else { vec![] };
+                    function.modifiers.push(ModifierInvocation { name: modifier, arguments });
+                    continue;
+                }
+            }
+            self.bump();
+        }
+        function.header = self.span_from(start);
+        if !self.eat(";") {
+            function.body = Some(self.block()?);
+        }
+        function.span = self.span_from(start);
+        Ok(function)
+    }
+
+    fn parameter_list(&mut self) -> ParseResult<Vec<Parameter>> {
+        self.expect("(")?;
+        let mut parameters = vec![];
+        while !self.at(")") {
+            parameters.push(self.parameter()?);
+            if !self.eat(",") {
+                break;
+            }
+        }
+        self.expect(")")?;
+        Ok(parameters)
+    }
+
+    fn parameter(&mut self) -> ParseResult<Parameter> {
+        let start = self.start();
+        self.type_name()?;
+        let location = self.data_location();
+        self.eat("indexed");
+        let name = if self.kind() == Some(TokenKind::Identifier) && !self.at(",") { Some(self.bump().to_string()) } else { None };
+        Ok(Parameter { location, name, span: self.span_from(start) })
+    }
+
+    fn data_location(&mut self) -> Option<DataLocation> {
+        let location = match self.peek() {
+            "memory" => DataLocation::Memory,
+            "storage" => DataLocation::Storage,
+            "calldata" => DataLocation::Calldata,
+            _ => return None,
+        };
+        self.bump();
+        Some(location)
+    }
+
+    fn type_name(&mut self) -> ParseResult<TypeName> {
+        let mut type_name = match self.peek() {
+            "mapping" => {
+                self.bump();
+                self.expect("(")?;
+                self.type_name()?;
+                if self.kind() == Some(TokenKind::Identifier) {
+                    self.bump();
+                }
+                self.expect("=>")?;
+                self.type_name()?;
+                if self.kind() == Some(TokenKind::Identifier) {
+                    self.bump();
+                }
+                self.expect(")")?;
+                TypeName::Mapping
+            }
+            "function" => {
+                self.bump();
+                self.parameter_list()?;
+                while matches!(self.peek(), "internal" | "external" | "pure" | "view" | "payable") {
+                    self.bump();
+                }
+                if self.eat("returns") {
+                    self.parameter_list()?;
+                }
+                TypeName::Function
+            }
+            word if is_elementary_type(word) && word != "payable" => {
+                self.bump();
+                if word == "address" {
+                    self.eat("payable");
+                }
+                TypeName::Elementary
+            }
+            word if RESERVED.contains(&word) => return Err(self.unexpected("a type")),
+            _ => {
+                self.path()?;
+                TypeName::UserDefined
+            }
+        };
+        while self.at("[") {
+            self.bump();
+            if !self.at("]") {
+                self.expression()?;
+            }
+            self.expect("]")?;
+            type_name = TypeName::Array;
+        }
+        Ok(type_name)
+    }
+
+    fn block(&mut self) -> ParseResult<Block> {
+        // `unchecked { ... }` is parsed as a plain block
+        self.eat("unchecked");
+        self.expect("{")?;
+        let mut statements = vec![];
+        while !self.at("}") && !self.at_end() {
+            let from = self.pos;
+            match self.statement() {
+                Ok(statement) => statements.push(statement),
+                Err(error) => self.recover(error, from),
+            }
+        }
+        self.expect("}")?;
+        Ok(Block { statements })
+    }
+
+    fn statement(&mut self) -> ParseResult<Statement> {
+        let kind = match self.peek() {
+            "{" => StatementKind::Block(self.block()?),
+            "unchecked" if self.peek_at(1) == "{" => StatementKind::Block(self.block()?),
+            "if" => {
+                self.bump();
+                self.expect("(")?;
+                let condition = self.expression()?;
+                self.expect(")")?;
+                let then = Box::new(self.statement()?);
+                let otherwise = if self.eat("else") { Some(Box::new(self.statement()?)) } else { None };
+                StatementKind::If(condition, then, otherwise)
+            }
+            "for" => {
+                self.bump();
+                self.expect("(")?;
+                let init = if self.eat(";") { None } else { Some(Box::new(self.simple_statement()?)) };
+                let condition = if self.at(";") { None } else { Some(self.expression()?) };
+                self.expect(";")?;
+                let update = if self.at(")") { None } else { Some(self.expression()?) };
+                self.expect(")")?;
+                StatementKind::For(init, condition, update, Box::new(self.statement()?))
+            }
+            "while" => {
+                self.bump();
+                self.expect("(")?;
+                let condition = self.expression()?;
+                self.expect(")")?;
+                StatementKind::While(condition, Box::new(self.statement()?))
+            }
+            "do" => {
+                self.bump();
+                let body = Box::new(self.statement()?);
+                self.expect("while")?;
+                self.expect("(")?;
+                let condition = self.expression()?;
+                self.expect(")")?;
+                self.expect(";")?;
+                StatementKind::DoWhile(body, condition)
+            }
+            "return" => {
+                self.bump();
+                let value = if self.at(";") { None } else { Some(self.expression()?) };
+                self.expect(";")?;
+                StatementKind::Return(value)
+            }
+            "emit" => {
+                self.bump();
+                let event = self.expression()?;
+                self.expect(";")?;
+                StatementKind::Emit(event)
+            }
+            // `revert CustomError(...)`, `revert(...)` is a plain call
+            "revert" if self.tokens.get(self.pos + 1).is_some_and(|token| token.kind == TokenKind::Identifier) => {
+                self.bump();
+                let error = self.expression()?;
+                self.expect(";")?;
+                StatementKind::Revert(error)
+            }
+            "try" => self.try_statement()?,
+            "assembly" => {
+                self.bump();
+                if self.kind() == Some(TokenKind::String) {
+                    self.bump();
+                }
+                if self.at("(") {
+                    self.skip_balanced("(", ")")?;
+                }
+                self.skip_balanced("{", "}")?;
+                StatementKind::Assembly
+            }
+            "break" | "continue" => {
+                let keyword = self.bump();
+                self.expect(";")?;
+                if keyword == "break" { StatementKind::Break } else { StatementKind::Continue }
+            }
+            "_" if self.peek_at(1) == ";" => {
+                self.pos += 2;
+                StatementKind::Placeholder
+            }
+            _ => return self.simple_statement(),
+        };
+        Ok(Statement { kind })
+    }
+
+    fn try_statement(&mut self) -> ParseResult<StatementKind> {
+        self.expect("try")?;
+        let call = self.expression()?;
+        let returns = if self.eat("returns") { self.parameter_list()? } else { vec![] };
+        let body = self.block()?;
+        let mut catches = vec![];
+        while self.eat("catch") {
+            if self.kind() == Some(TokenKind::Identifier) {
+                self.bump();
+            }
+            if self.at("(") {
+                self.parameter_list()?;
+            }
+            catches.push(self.block()?);
+        }
+        Ok(StatementKind::Try(call, returns, body, catches))
+    }
+
+    // a variable declaration or an expression, followed by `;`
+    fn simple_statement(&mut self) -> ParseResult<Statement> {
+        let from = self.pos;
+        let kind = match self.variable_declaration() {
+            Some(declarations) => {
+                let value = if self.eat("=") { Some(self.expression()?) } else { None };
+                StatementKind::VariableDeclaration(declarations, value)
+            }
+            None => {
+                self.pos = from;
+                StatementKind::Expression(self.expression()?)
+            }
+        };
+        self.expect(";")?;
+        Ok(Statement { kind })
+    }
+
+    // parses the left side of a declaration, `uint a` or `(uint a, , bool b)`
+    // returns None when the statement is not a declaration, the caller rewinds
+    fn variable_declaration(&mut self) -> Option<Vec<Option<Parameter>>> {
+        if self.eat("(") {
+            let mut declarations = vec![];
+            loop {
+                if self.at(",") || self.at(")") {
+                    declarations.push(None);
+                } else {
+                    let declaration = self.parameter().ok()?;
+                    declaration.name.as_ref()?;
+                    declarations.push(Some(declaration));
+                }
+                if !self.eat(",") {
+                    break;
+                }
+            }
+            if !self.eat(")") || !self.at("=") {
+                return None;
+            }
+            return Some(declarations);
+        }
+        let declaration = self.parameter().ok()?;
+        declaration.name.as_ref()?;
+        if !self.at("=") && !self.at(";") {
+            return None;
+        }
+        Some(vec![Some(declaration)])
+    }
+
+    fn expression(&mut self) -> ParseResult<Expression> {
+        let start = self.start();
+        let left = self.conditional()?;
+        if ASSIGNMENT_OPERATORS.contains(&self.peek()) {
+            let operator = self.bump().to_string();
+            let right = self.expression()?;
+            return Ok(Expression { kind: ExpressionKind::Assign(operator, Box::new(left), Box::new(right)), span: self.span_from(start) });
+        }
+        Ok(left)
+    }
+
+    fn conditional(&mut self) -> ParseResult<Expression> {
+        let start = self.start();
+        let condition = self.binary(0)?;
+        if !self.eat("?") {
+            return Ok(condition);
+        }
+        let then = self.expression()?;
+        self.expect(":")?;
+        let otherwise = self.expression()?;
+        Ok(Expression { kind: ExpressionKind::Conditional(Box::new(condition), Box::new(then), Box::new(otherwise)), span: self.span_from(start) })
+    }
+
+    // binary operators by precedence climbing, `**` is right associative
+    fn binary(&mut self, min_precedence: u8) -> ParseResult<Expression> {
+        let start = self.start();
+        let mut left = self.unary()?;
+        while let Some(precedence) = binary_precedence(self.peek()).filter(|precedence| *precedence >= min_precedence) {
+            let operator = self.bump().to_string();
+            let next = if operator == "**" { precedence } else { precedence + 1 };
+            let right = self.binary(next)?;
+            left = Expression { kind: ExpressionKind::Binary(operator, Box::new(left), Box::new(right)), span: self.span_from(start) };
+        }
+        Ok(left)
+    }
+
+    fn unary(&mut self) -> ParseResult<Expression> {
+        let start = self.start();
+        if matches!(self.peek(), "!" | "~" | "-" | "+" | "++" | "--" | "delete") {
+            let operator = self.bump().to_string();
+            let operand = self.unary()?;
+            return Ok(Expression { kind: ExpressionKind::Unary(operator, Box::new(operand)), span: self.span_from(start) });
+        }
+        let primary = self.primary()?;
+        self.postfix(primary, start)
+    }
+
+    fn postfix(&mut self, mut expression: Expression, start: usize) -> ParseResult<Expression> {
+        loop {
+            let kind = match self.peek() {
+                "." => {
+                    self.bump();
+                    let member = self.identifier()?;
+                    ExpressionKind::Member(Box::new(expression), member)
+                }
+                "[" => {
+                    self.bump();
+                    let index = if self.at("]") || self.at(":") { None } else { Some(Box::new(self.expression()?)) };
+                    let kind = if self.eat(":") {
+                        let end = if self.at("]") { None } else { Some(Box::new(self.expression()?)) };
+                        ExpressionKind::Slice(Box::new(expression), index, end)
+                    } else {
+                        ExpressionKind::Index(Box::new(expression), index)
+                    };
+                    self.expect("]")?;
+                    kind
+                }
+                "(" => ExpressionKind::Call(Box::new(expression), self.call_arguments()?),
+                // `{value: x}` call options, a `{` followed by anything else opens a block
+                "{" if self.peek_at(2) == ":" => {
+                    self.bump();
+                    let mut options = vec![];
+                    while !self.at("}") {
+                        let name = self.identifier()?;
+                        self.expect(":")?;
+                        options.push((name, self.expression()?));
+                        if !self.eat(",") {
+                            break;
+                        }
+                    }
+                    self.expect("}")?;
+                    ExpressionKind::CallOptions(Box::new(expression), options)
+                }
+                "++" | "--" => ExpressionKind::Unary(self.bump().to_string(), Box::new(expression)),
+                _ => return Ok(expression),
+            };
+            expression = Expression { kind, span: self.span_from(start) };
+        }
+    }
+
+    // `(a, b)` or `({a: x, b: y})`, named arguments are kept in order
+    fn call_arguments(&mut self) -> ParseResult<Vec<Expression>> {
+        self.expect("(")?;
+        let mut arguments = vec![];
+        let named = self.eat("{");
+        while !self.at(")") && !self.at("}") {
+            if named {
+                self.identifier()?;
+                self.expect(":")?;
+            }
+            arguments.push(self.expression()?);
+            if !self.eat(",") {
+                break;
+            }
+        }
+        if named {
+            self.expect("}")?;
+        }
+        self.expect(")")?;
+        Ok(arguments)
+    }
+
+    fn primary(&mut self) -> ParseResult<Expression> {
+        let start = self.start();
+        let kind = match (self.kind(), self.peek()) {
+            (Some(TokenKind::Punct), "(") => {
+                self.bump();
+                let mut elements = vec![];
+                loop {
+                    if self.at(",") || self.at(")") {
+                        elements.push(None);
+                    } else {
+                        elements.push(Some(self.expression()?));
+                    }
+                    if !self.eat(",") {
+                        break;
+                    }
+                }
+                self.expect(")")?;
+                if elements.len() == 1 {
+                    if let Some(Some(inner)) = elements.pop() {
+                        return Ok(inner);
+                    }
+                    elements.push(None);
+                }
+                ExpressionKind::Tuple(elements)
+            }
+            (Some(TokenKind::Punct), "[") => {
+                self.bump();
+                let mut elements = vec![];
+                while !self.at("]") {
+                    elements.push(self.expression()?);
+                    if !self.eat(",") {
+                        break;
+                    }
+                }
+                self.expect("]")?;
+                ExpressionKind::Array(elements)
+            }
+            (Some(TokenKind::Number), _) => {
+                self.bump();
+                if UNITS.contains(&self.peek()) {
+                    self.bump();
+                }
+                ExpressionKind::Literal(self.span_from(start).text(self.source).to_string())
+            }
+            (Some(TokenKind::String), _) => {
+                while self.kind() == Some(TokenKind::String) {
+                    self.bump();
+                }
+                ExpressionKind::Literal(self.span_from(start).text(self.source).to_string())
+            }
+            (Some(TokenKind::Identifier), "true" | "false") => ExpressionKind::Literal(self.bump().to_string()),
+            (Some(TokenKind::Identifier), "new") => {
+                self.bump();
+                self.type_name()?;
+                ExpressionKind::New
+            }
+            (Some(TokenKind::Identifier), word) if is_elementary_type(word) => {
+                self.bump();
+                ExpressionKind::ElementaryType
+            }
+            (Some(TokenKind::Identifier), _) => ExpressionKind::Identifier(self.bump().to_string()),
+            _ => return Err(self.unexpected("an expression")),
+        };
+        Ok(Expression { kind, span: self.span_from(start) })
+    }
+}
+
+// precedence of a binary operator, higher binds tighter
+fn binary_precedence(operator: &str) -> Option<u8> {
+    Some(match operator {
+        "||" => 1,
+        "&&" => 2,
+        "==" | "!=" => 3,
+        "<" | ">" | "<=" | ">=" => 4,
+        "|" => 5,
+        "^" => 6,
+        "&" => 7,
+        "<<" | ">>" | ">>>" => 8,
+        "+" | "-" => 9,
+        "*" | "/" | "%" => 10,
+        "**" => 11,
+        _ => return None,
+    })
+}
+
+// `address`, `bool`, `uint256`, `bytes32`...
+fn is_elementary_type(word: &str) -> bool {
+    let sized = |prefix: &str| word.strip_prefix(prefix).is_some_and(|size| size.chars().all(|c| c.is_ascii_digit()));
+    matches!(word, "address" | "bool" | "string" | "payable")
+        || sized("uint") || sized("int") || sized("bytes")
+        || word == "byte"
+        || ["fixed", "ufixed"].iter().any(|prefix| word.strip_prefix(prefix).is_some_and(|size| size.chars().all(|c| c.is_ascii_digit() || c == 'x')))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // returns the statements of the first function of the first contract, failing on parse errors
+    fn statements(source: &str) -> Vec<Statement> {
+        let (unit, errors) = parse(source);
+        assert_eq!(errors, Vec::<String>::new());
+        unit.contracts[0].functions[0].body.clone().expect("the function has a body").statements
+    }
+
+    #[test]
+    fn contracts_functions_and_modifiers() {
+        let source = [
+            "abstract contract Vault is Ownable, ReentrancyGuard {",
+            "    mapping(address => uint256) balances;",
+            "    modifier onlyAdmin() { require(msg.sender == admin); _; }",
+            "    constructor() Ownable(msg.sender) {}",
+            "    function withdraw(uint256 amount) external nonReentrant onlyAdmin returns (bool) {}",
+            "}",
+        ].join("\n");
+        let (unit, errors) = parse(&source);
+        assert!(errors.is_empty());
+        let contract = &unit.contracts[0];
+        assert_eq!((contract.kind, contract.name.as_str()), (ContractKind::AbstractContract, "Vault"));
+        assert_eq!(contract.bases.iter().map(|base| base.name.as_str()).collect::<Vec<_>>(), ["Ownable", "ReentrancyGuard"]);
+        assert_eq!(contract.state_variables[0].type_name, TypeName::Mapping);
+
+        let modifier = &contract.modifiers[0];
+        assert_eq!((modifier.kind, modifier.display_name()), (FunctionKind::Modifier, "onlyAdmin".to_string()));
+        let body = &modifier.body.as_ref().expect("the modifier has a body").statements;
+        assert!(matches!(body[1].kind, StatementKind::Placeholder));
+
+        let names: Vec<String> = contract.functions.iter().map(|function| function.display_name()).collect();
+        assert_eq!(names, ["constructor()", "withdraw()"]);
+        assert!(contract.functions[0].has_modifier("Ownable"));
+        let withdraw = &contract.functions[1];
+        assert_eq!(withdraw.visibility, Some(Visibility::External));
+        assert_eq!(withdraw.modifiers.iter().map(|modifier| modifier.name.as_str()).collect::<Vec<_>>(), ["nonReentrant", "onlyAdmin"]);
+        assert_eq!((withdraw.parameters.len(), withdraw.returns.len()), (1, 1));
+    }
+
+    // `(bool ok, ) = ...` declares one variable and leaves a slot empty, `{value: x}` is kept as call options
+    #[test]
+    fn tuple_destructuring_and_call_options() {
+        let statements = statements("contract A { function f() external { (bool ok, ) = to.call{value: amount}(\"\"); (ok, ) = to.call(data); } }");
+        let StatementKind::VariableDeclaration(variables, Some(value)) = &statements[0].kind else { panic!("not a declaration: {:?}", statements[0]) };
+        assert_eq!(variables.len(), 2);
+        assert_eq!(variables[0].as_ref().and_then(|variable| variable.name.as_deref()), Some("ok"));
+        assert!(variables[1].is_none());
+        let ExpressionKind::Call(callee, arguments) = &value.kind else { panic!("not a call: {:?}", value) };
+        assert_eq!(arguments.len(), 1);
+        let ExpressionKind::CallOptions(target, options) = &callee.kind else { panic!("no call options: {:?}", callee) };
+        assert_eq!(target.member_name(), Some("call"));
+        assert_eq!(options.iter().map(|(name, _)| name.as_str()).collect::<Vec<_>>(), ["value"]);
+
+        let StatementKind::Expression(Expression { kind: ExpressionKind::Assign(operator, target, _), .. }) = &statements[1].kind else { panic!("not an assignment: {:?}", statements[1]) };
+        assert_eq!(operator, "=");
+        assert!(matches!(&target.kind, ExpressionKind::Tuple(elements) if elements.len() == 2 && elements[1].is_none()));
+    }
+
+    // an `unchecked` block is parsed as a plain block
+    #[test]
+    fn unchecked_block() {
+        let statements = statements("contract A { function f(uint256 n) external { unchecked { for (uint256 i; i < n; ++i) { total += i; } } } }");
+        let StatementKind::Block(block) = &statements[0].kind else { panic!("not a block: {:?}", statements[0]) };
+        let StatementKind::For(_, Some(condition), Some(step), _) = &block.statements[0].kind else { panic!("not a loop: {:?}", block.statements[0]) };
+        assert!(matches!(&condition.kind, ExpressionKind::Binary(operator, ..) if operator == "<"));
+        assert!(matches!(&step.kind, ExpressionKind::Unary(operator, _) if operator == "++"));
+    }
+
+    // a statement and a declaration that can not be parsed are skipped and reported with their line,
+    // the rest of the file is still parsed
+    #[test]
+    fn recovers_from_malformed_input() {
+        let source = [
+            "contract A {",
+            "    function f() external { uint256 x = ; x = 1; }",
+            "    function ) broken;",
+            "    function g() external {}",
+            "}",
+        ].join("\n");
+        let (unit, errors) = parse(&source);
+        assert_eq!(errors.len(), 2);
+        assert!(errors[0].starts_with("line 2:") && errors[1].starts_with("line 3:"), "{:?}", errors);
+        let functions = &unit.contracts[0].functions;
+        assert_eq!(functions.iter().map(|function| function.display_name()).collect::<Vec<_>>(), ["f()", "g()"]);
+        let body = &functions[0].body.as_ref().expect("the function has a body").statements;
+        assert!(matches!(&body[..], [Statement { kind: StatementKind::Expression(Expression { kind: ExpressionKind::Assign(..), .. }) }]));
+    }
+}
@@ -0,0 +1,127 @@
+use crate::finding::Finding;
+use crate::regexrules::Rule;
+use crate::tokenizer::SourceMap;
+
+// Findings silenced in a file with `auditooor-disable` comments:
+// `auditooor-disable-next-line [IDS]`, `auditooor-disable-line [IDS]` and
+// `auditooor-disable [IDS]` ... `auditooor-enable [IDS]` blocks
+// ids are separated by spaces or commas, a `--` starts a free-form reason
+#[derive(Default)]
+pub struct Suppressions {
+    // byte ranges and the rule they silence, None silences every rule
+    ranges: Vec<(usize, usize, Option<String>)>,
+}
+
+impl Suppressions {
+    // reads the suppression comments of a file
+    pub fn new(source: &str, source_map: &SourceMap) -> Suppressions {
+        let mut ranges = vec![];
+        // blocks opened by `auditooor-disable`, with where they start
+        let mut open: Vec<(usize, Option<String>)> = vec![];
+        for (start, end) in source_map.comments() {
+            let text = source[start..end].trim_end_matches("*/").trim_start_matches('/').trim_start_matches('*');
+            let mut words = text.split_whitespace();
+            let directive = words.next().unwrap_or_default();
+            let ids: Vec<String> = words.take_while(|word| *word != "--")
+                .flat_map(|word| word.split(','))
+                .filter(|id| !id.is_empty())
+                .map(|id| id.to_string())
+                .collect();
+            let ids: Vec<Option<String>> = if ids.is_empty() { vec![None] } else { ids.into_iter().map(Some).collect() };
+
+            match directive {
+                "auditooor-disable-line" => {
+                    let (line_start, line_end) = (line_start(source, start), line_end(source, end));
+                    ranges.extend(ids.into_iter().map(|id| (line_start, line_end, id)));
+                }
+                "auditooor-disable-next-line" => {
+                    let next_line = (line_end(source, end) + 1).min(source.len());
+                    let next_line_end = line_end(source, next_line);
+                    ranges.extend(ids.into_iter().map(|id| (next_line, next_line_end, id)));
+                }
+                "auditooor-disable" => {
+                    for id in ids {
+                        if !open.iter().any(|(_, open_id)| same_id(open_id, &id)) {
+                            open.push((end, id));
+                        }
+                    }
+                }
+                "auditooor-enable" => {
+                    let (closed, still_open): (Vec<_>, Vec<_>) = open.into_iter().partition(|(_, open_id)| ids.contains(&None) || ids.iter().any(|id| same_id(id, open_id)));
+                    ranges.extend(closed.into_iter().map(|(block_start, id)| (block_start, start, id)));
+                    open = still_open;
+                }
+                _ => {}
+            }
+        }
+        // blocks that are never enabled again run to the end of the file
+        ranges.extend(open.into_iter().map(|(block_start, id)| (block_start, source.len(), id)));
+        Suppressions { ranges }
+    }
+
+    // returns true if the finding is silenced, by where its first non-blank character is
+    pub fn suppresses(&self, finding: &Finding) -> bool {
+        let offset = finding.anchor();
+        self.ranges.iter().any(|(start, end, id)| {
+            *start <= offset && offset < *end && id.as_ref().is_none_or(|id| id.eq_ignore_ascii_case(&finding.rule_id))
+        })
+    }
+}
+
+// returns true if two ids of suppression comments name the same rule, ignoring case
+fn same_id(id: &Option<String>, other: &Option<String>) -> bool {
+    match (id, other) {
+        (Some(id), Some(other)) => id.eq_ignore_ascii_case(other),
+        (id, other) => id.is_none() && other.is_none(),
+    }
+}
+
+// returns the offset of the start of the line containing `offset`
+fn line_start(source: &str, offset: usize) -> usize {
+    source[..offset].rfind('\n').map(|i| i + 1).unwrap_or(0)
+}
+
+// returns the offset of the end of the line containing `offset`, before its `\n`
+fn line_end(source: &str, offset: usize) -> usize {
+    source[offset..].find('\n').map(|i| offset + i).unwrap_or(source.len())
+}
+
+// returns how many findings of each rule were suppressed, in rule order, skipping rules without any
+pub fn counts<'a>(rules: &[&'a Rule], suppressed: &[Finding]) -> Vec<(&'a Rule, usize)> {
+    rules.iter()
+        .map(|rule| (*rule, suppressed.iter().filter(|finding| finding.rule_id == rule.id).count()))
+        .filter(|(_, count)| *count > 0)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::regexrules::Severity;
+
+    // returns a finding of a rule starting at `start`
+    fn finding(rule_id: &str, source: &str, start: usize) -> Finding {
+        Finding::new(rule_id, Severity::Gas, "A.sol", source, start, start + 1)
+    }
+
+    // rule ids are matched whatever their case, in single lines and in blocks
+    #[test]
+    fn ids_ignore_case() {
+        let source = [
+            "contract A {",
+            "    // auditooor-disable-next-line g-02",
+            "    function f() external { for (uint256 i = 0; i < a.length; i++) {} }",
+            "    /* auditooor-disable h-01 */",
+            "    function g() external { (bool ok, ) = to.call(data); }",
+            "    // auditooor-enable H-01",
+            "    function h() external { (bool ok, ) = to.call(data); }",
+            "}",
+        ].join("\n");
+        let suppressions = Suppressions::new(&source, &SourceMap::new(&source));
+        let (loop_condition, first_call, last_call) = (source.find("i < a").unwrap(), source.find("to.call").unwrap(), source.rfind("to.call").unwrap());
+        assert!(suppressions.suppresses(&finding("G-02", &source, loop_condition)));
+        assert!(!suppressions.suppresses(&finding("G-01", &source, loop_condition)));
+        assert!(suppressions.suppresses(&finding("H-01", &source, first_call)));
+        assert!(!suppressions.suppresses(&finding("H-01", &source, last_call)));
+    }
+}
@@ -0,0 +1,82 @@
+use serde::Deserialize;
+
+// Kind of source text a byte offset belongs to
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Region {
+    Code,
+    Comment,
+    String,
+}
+
+// Comment and string literal ranges of a Solidity file
+// everything outside of the ranges is code
+#[derive(Clone, Debug, Default)]
+pub struct SourceMap {
+    ranges: Vec<(usize, usize, Region)>,
+}
+
+impl SourceMap {
+    // scans a Solidity file for comments (`//`, `/* */`, NatSpec) and
+    // string literals (`"..."`, `'...'`, `unicode"..."`, `hex"..."`)
+    pub fn new(source: &str) -> SourceMap {
+        let bytes = source.as_bytes();
+        let mut ranges = vec![];
+        let mut i = 0;
+        while i < bytes.len() {
+            match bytes[i] {
+                b'/' if bytes.get(i + 1) == Some(&b'/') => {
+                    let end = source[i..].find('\n').map(|n| i + n).unwrap_or(bytes.len());
+                    ranges.push((i, end, Region::Comment));
+                    i = end;
+                }
+                b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                    let end = source[i + 2..].find("*/").map(|n| i + 2 + n + 2).unwrap_or(bytes.len());
+                    ranges.push((i, end, Region::Comment));
+                    i = end;
+                }
+                quote @ (b'"' | b'\'') => {
+                    let mut end = i + 1;
+                    while end < bytes.len() && bytes[end] != quote && bytes[end] != b'\n' {
+                        end += if bytes[end] == b'\\' { 2 } else { 1 };
+                    }
+                    let end = (end + 1).min(bytes.len());
+                    ranges.push((i, end, Region::String));
+                    i = end;
+                }
+                _ => i += 1,
+            }
+        }
+        SourceMap { ranges }
+    }
+
+    // returns the kind of text at a byte offset
+    pub fn region_at(&self, offset: usize) -> Region {
+        let index = self.ranges.partition_point(|(start, _, _)| *start <= offset);
+        match index.checked_sub(1).map(|i| self.ranges[i]) {
+            Some((_, end, region)) if offset < end => region,
+            _ => Region::Code,
+        }
+    }
+
+    // returns the byte range of every comment, in order
+    pub fn comments(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.ranges.iter().filter(|(_, _, region)| *region == Region::Comment).map(|(start, end, _)| (*start, *end))
+    }
+
+    // counts the normalized source lines of code of a file:
+    // lines with anything other than blanks and comments
+    pub fn nsloc(&self, source: &str) -> usize {
+        let mut count = 0;
+        let mut line_start = 0;
+        for line in source.split('\n') {
+            let is_code = line.char_indices()
+                .any(|(i, c)| !c.is_whitespace() && self.region_at(line_start + i) != Region::Comment);
+            if is_code {
+                count += 1;
+            }
+            line_start += line.len() + 1;
+        }
+        count
+    }
+}